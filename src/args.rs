@@ -1,6 +1,7 @@
 //! Handles everything related to CLI arguments
 
-use clap::{Args, Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -8,37 +9,293 @@ pub struct Arguments {
     /// Operation to perform
     #[clap(subcommand)]
     pub operation: Operation,
+    /// Format of everything printed to `stdout`
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text
+    Text,
+    /// A single JSON document, human readable messages go to `stderr`
+    Json,
+    /// One JSON document per line, human readable messages go to `stderr`
+    Ndjson,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Operation {
-    /// Add videos to existing playlist or create new
-    Add(ModifyArgs),
+    /// Create a new, empty playlist
+    Create(CreateArgs),
+    /// Add videos to an existing playlist
+    Add(AddArgs),
     /// Remove videos from existing playlist
     Remove(ModifyArgs),
     /// Print playlist URL of an existing playlist or list of IDs
     Print(PrintArgs),
     /// List existing playlists
-    List,
+    List(ListArgs),
+    /// Re-fetch meta data of existing playlists and report changes
+    Refresh(RefreshArgs),
+    /// Import an existing YouTube playlist or a playlist file
+    Import(ImportArgs),
+    /// Export an existing playlist to a playlist file
+    Export(ExportArgs),
+    /// Rename an existing playlist
+    Rename(CopyArgs),
+    /// Copy an existing playlist to a new title
+    Copy(CopyArgs),
+    /// Delete an existing playlist
+    Delete(DeleteArgs),
+    /// Create a playlist containing the videos of all given playlists
+    Union(SetArgs),
+    /// Create a playlist containing the videos that are part of all given playlists
+    Intersect(SetArgs),
+    /// Create a playlist containing the videos of the first playlist that aren't part of the others
+    Difference(SetArgs),
+    /// Find the playlists containing a video
+    Which(WhichArgs),
+    /// Search titles, channel names, descriptions and tags of all saved videos
+    SearchLocal(SearchLocalArgs),
+    /// Search YouTube for videos and optionally add them to a playlist
+    Search(SearchArgs),
+    /// List past states of a playlist
+    History(HistoryArgs),
+    /// Undo the last change of a playlist
+    Undo(HistoryArgs),
+    /// Restore a past state of a playlist, as numbered by `history`
+    Restore(RestoreArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CreateArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+}
+
+#[derive(Debug, Args)]
+#[group(skip)]
+#[command(group(ArgGroup::new("id_sources").required(true).multiple(true).args(["ids", "ids_file"])))]
+pub struct AddArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// Space separated list of video IDs or URLs (`-` reads the list from `stdin`)
+    #[arg(short, long, num_args = 1.., value_delimiter = ' ')]
+    pub ids: Vec<String>,
+    /// File containing video IDs or URLs separated by whitespace, line breaks or commas
+    #[arg(long)]
+    pub ids_file: Option<PathBuf>,
+    /// Create the playlist if it doesn't exist yet
+    #[arg(short, long)]
+    pub create: bool,
 }
 
 #[derive(Debug, Args)]
+#[group(skip)]
+#[command(group(ArgGroup::new("id_sources").required(true).multiple(true).args(["ids", "ids_file"])))]
 pub struct ModifyArgs {
     /// Title of the playlist
     #[arg(short = 't', long, required = true)]
     pub playlist_title: String,
-    /// Space separated list of video IDs
-    #[arg(short, long, num_args = 1.., value_delimiter = ' ', required = true)]
+    /// Space separated list of video IDs or URLs (`-` reads the list from `stdin`)
+    #[arg(short, long, num_args = 1.., value_delimiter = ' ')]
     pub ids: Vec<String>,
+    /// File containing video IDs or URLs separated by whitespace, line breaks or commas
+    #[arg(long)]
+    pub ids_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
-#[group(multiple = false, required = true)]
+#[group(skip)]
+#[command(group(ArgGroup::new("sources").required(true).args(["playlist_title", "ids", "ids_file"])))]
 pub struct PrintArgs {
     /// Title of the playlist
-    #[arg(short = 't', long, required = false)]
+    #[arg(short = 't', long)]
     pub playlist_title: Option<String>,
-    /// Space separated list of video IDs
-    #[arg(short, long, num_args = 1.., value_delimiter = ' ', required = false)]
+    /// Space separated list of video IDs or URLs (`-` reads the list from `stdin`)
+    #[arg(short, long, num_args = 1.., value_delimiter = ' ')]
     pub ids: Option<Vec<String>>,
+    /// File containing video IDs or URLs separated by whitespace, line breaks or commas
+    #[arg(long)]
+    pub ids_file: Option<PathBuf>,
+    /// Template for each video (e.g. "{index}. {title} [{duration}] {url}") or name of a template saved in the config
+    #[arg(long)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Order of the playlists
+    #[arg(short, long, value_enum, default_value_t = ListSort::Name)]
+    pub sort: ListSort,
+    /// Template for each playlist (e.g. "{playlist_title} ({num_items})") or name of a template saved in the config
+    #[arg(long)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Args)]
+#[group(multiple = false, required = true)]
+pub struct RefreshArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = false)]
+    pub playlist_title: Option<String>,
+    /// Refresh all existing playlists
+    #[arg(short, long, required = false)]
+    pub all: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListSort {
+    /// Alphabetically by title
+    Name,
+    /// Largest number of videos first
+    Size,
+    /// Most recently modified first
+    Modified,
+    /// Longest total duration first
+    Duration,
+}
+
+#[derive(Debug, Args)]
+#[group(skip)]
+#[command(group(ArgGroup::new("source").required(true).args(["list", "file"])))]
+pub struct ImportArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// ID or URL of the YouTube playlist
+    #[arg(short, long)]
+    pub list: Option<String>,
+    /// Path to a playlist file
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+    /// Format of the playlist file (determined by the file extension if omitted)
+    #[arg(short, long, conflicts_with = "list")]
+    pub format: Option<FileFormat>,
+    /// Map video fields to CSV columns as `<field>=<column>` (e.g. `--column "id=Video ID"`)
+    ///
+    /// Fields: id, url, title, published_at, duration, channel_title
+    #[arg(long = "column", conflicts_with = "list")]
+    pub columns: Vec<String>,
+    /// Merge into an existing playlist instead of creating a new one
+    #[arg(short, long)]
+    pub merge: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// Format of the playlist file
+    #[arg(short, long, required = true)]
+    pub format: FileFormat,
+    /// Path of the playlist file (prints to `stdout` if omitted)
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct CopyArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// New title of the playlist
+    #[arg(short, long, required = true)]
+    pub new_title: String,
+    /// Overwrite an existing playlist with the new title
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SetArgs {
+    /// Titles of the playlists, the order of the first one is kept
+    #[arg(short = 't', long = "playlist-titles", required = true, num_args = 1..)]
+    pub playlist_titles: Vec<String>,
+    /// Title of the resulting playlist
+    #[arg(short, long, required = true)]
+    pub new_title: String,
+    /// Overwrite an existing playlist with the new title
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+#[group(multiple = false, required = true)]
+pub struct WhichArgs {
+    /// Video ID or URL
+    pub video: Option<String>,
+    /// Report all videos that are part of more than one playlist
+    #[arg(long)]
+    pub dupes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchLocalArgs {
+    /// Text to search for (case-insensitive)
+    pub query: String,
+    /// Also match words with small typos
+    #[arg(long)]
+    pub fuzzy: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Search terms
+    pub query: String,
+    /// Maximum number of results
+    #[arg(short, long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(1..=50))]
+    pub limit: u8,
+    /// Title of an existing playlist to add the results to
+    #[arg(short, long)]
+    pub add_to: Option<String>,
+    /// Space separated ranks of the results to add (adds all results if omitted)
+    #[arg(short, long, num_args = 1.., value_delimiter = ' ', requires = "add_to")]
+    pub select: Vec<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// Title of the playlist
+    #[arg(short = 't', long, required = true)]
+    pub playlist_title: String,
+    /// Number of the past state as listed by `history` (1 is the state before the last change)
+    pub number: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FileFormat {
+    /// Extended M3U (also used for `.m3u8`)
+    #[value(alias = "m3u8")]
+    M3u,
+    /// XML Shareable Playlist Format
+    Xspf,
+    /// JSON Shareable Playlist Format
+    Jspf,
+    /// Comma separated values with a header row
+    Csv,
+    /// Plain text with one video ID or URL per line
+    #[value(alias = "txt")]
+    Text,
 }
@@ -2,20 +2,35 @@
 
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     // E.g. "KiasdlLLkgUUIOOsd-7ASGkdskgT9ka9JlsdgkP" <- just an example key
     pub youtube_api_key: String,
+    // E.g. "https://youtube.googleapis.com/youtube/v3" or a local mock server
+    pub youtube_api_url: String,
     // E.g. "$XDG_DATA_HOME/oxysound/playlists"
     pub save_directory: String,
+    // Number of past states kept per playlist for `undo` and `restore`, 0 keeps all
+    pub history_max_entries: usize,
+    // Number of days past states are kept, 0 keeps them forever
+    pub history_max_age_days: u64,
+    // E.g. `short = "{index}. {title} [{duration}] {url}"`, used via `--format short`
+    // Has to be the last field, TOML tables can't be followed by plain values
+    pub templates: BTreeMap<String, String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             youtube_api_key: "".into(),
+            youtube_api_url: "https://youtube.googleapis.com/youtube/v3".into(),
             save_directory: "$XDG_DATA_HOME/oxysound/playlists".into(),
+            history_max_entries: 50,
+            history_max_age_days: 90,
+            templates: BTreeMap::new(),
         }
     }
 }
@@ -38,6 +53,12 @@ impl Config {
                 config_file_path,
             ));
         }
+        if self.youtube_api_url.is_empty() {
+            return Err(Error::MissingConfig(
+                "youtube_api_url".to_string(),
+                config_file_path,
+            ));
+        }
         if self.save_directory.is_empty() {
             return Err(Error::MissingConfig(
                 "save_directory".to_string(),
@@ -47,3 +68,25 @@ impl Config {
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_serializes() {
+        let config = Config {
+            templates: BTreeMap::from([("short".to_string(), "{index}. {title}".to_string())]),
+            ..Default::default()
+        };
+        let config_path =
+            std::env::temp_dir().join(format!("oxysound-test-config-{}.toml", std::process::id()));
+
+        confy::store_path(&config_path, &config).expect("Config serializes to TOML");
+        let loaded: Config = confy::load_path(&config_path).expect("Config deserializes from TOML");
+        assert_eq!(loaded.history_max_entries, 50);
+        assert_eq!(loaded.templates, config.templates);
+
+        std::fs::remove_file(config_path).expect("Config file was written");
+    }
+}
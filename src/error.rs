@@ -8,8 +8,38 @@ pub enum Error {
     #[error("Couldn't convert `PathBuf` to `String`: {0}")]
     StringFromPathBuf(String),
 
-    #[error("Response didn't yield enough items (expected: {0}, found: {1}")]
-    NotEnoughResponseItems(u8, u8),
+    #[error("Invalid video IDs or URLs: {}", .0.join(", "))]
+    InvalidVideoIds(Vec<String>),
+
+    #[error("Invalid playlist ID or URL: {0}")]
+    InvalidPlaylistId(String),
+
+    #[error("Playlist already exists: {0}")]
+    PlaylistAlreadyExists(String),
+
+    #[error("Playlist does not exist: {0}")]
+    PlaylistNotFound(String),
+
+    #[error("Couldn't determine the format of {0}, specify it with `--format`")]
+    UnknownFileFormat(String),
+
+    #[error("Invalid CSV column mapping: {0}")]
+    InvalidColumnMapping(String),
+
+    #[error("No search results with ranks: {}", .0.iter().map(usize::to_string).collect::<Vec<String>>().join(", "))]
+    InvalidSearchSelection(Vec<usize>),
+
+    #[error("Playlist file has schema version {0}, but this version of oxysound only supports up to version {1}, please update oxysound")]
+    UnsupportedSchemaVersion(u64, u64),
+
+    #[error("Playlist has no history to undo: {0}")]
+    EmptyHistory(String),
+
+    #[error("No history entry with number: {0}, see `oxysound history`")]
+    InvalidHistoryEntry(usize),
+
+    #[error("Invalid output template: {0}")]
+    InvalidTemplate(String),
 
     #[error("Request failed {0}")]
     Request(#[from] reqwest::Error),
@@ -20,6 +50,12 @@ pub enum Error {
     #[error("Failed to serialize/deserialize JSON")]
     Json(#[from] serde_json::Error),
 
+    #[error("Failed to read/write CSV")]
+    Csv(#[from] csv::Error),
+
+    #[error("Failed to serialize/deserialize XML")]
+    Xml(#[from] quick_xml::DeError),
+
     #[error("Failed loading config")]
     Config(#[from] confy::ConfyError),
 }
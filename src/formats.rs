@@ -0,0 +1,105 @@
+//! Conversion of playlists from and to other playlist file formats
+
+use crate::args::FileFormat;
+use crate::ids;
+use crate::playlist::{Playlist, Video};
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub mod csv;
+pub mod jspf;
+pub mod m3u;
+pub mod text;
+pub mod xspf;
+
+/// Data structure for a track, shared by XSPF and JSPF
+///
+/// Durations are given in milliseconds
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Track {
+    #[serde(default)]
+    pub location: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+}
+
+impl From<&Video> for Track {
+    fn from(value: &Video) -> Self {
+        let non_empty = |text: &str| (!text.is_empty()).then(|| text.to_string());
+
+        Self {
+            location: vec![value.url().to_string()],
+            title: non_empty(value.title()),
+            creator: non_empty(value.channel_title()),
+            // Durations too long to be represented in milliseconds are left out
+            duration: value
+                .duration_seconds()
+                .and_then(|seconds| seconds.checked_mul(1000)),
+        }
+    }
+}
+
+impl Track {
+    /// Return the track as video, if one of its locations is a YouTube URL
+    pub fn into_video(self) -> Option<Video> {
+        let id = self
+            .location
+            .iter()
+            .find_map(|location| ids::parse_video_id(location))?;
+
+        Some(Video::from_entry(
+            id,
+            self.title.unwrap_or_default(),
+            self.duration.map(|milliseconds| milliseconds / 1000),
+            self.creator.unwrap_or_default(),
+        ))
+    }
+}
+
+impl FileFormat {
+    /// Return the format matching the extension of `file_path`, if any
+    /// * `file_path` - path to a playlist file
+    pub fn from_path(file_path: &Path) -> Option<Self> {
+        let extension = file_path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "m3u" | "m3u8" => Some(FileFormat::M3u),
+            "xspf" => Some(FileFormat::Xspf),
+            "jspf" => Some(FileFormat::Jspf),
+            "csv" => Some(FileFormat::Csv),
+            "txt" => Some(FileFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Return the content of a playlist file representing `playlist`
+/// * `playlist` - playlist to export
+/// * `format` - format of the playlist file
+pub fn export(playlist: &Playlist, format: FileFormat) -> Result<String> {
+    match format {
+        FileFormat::M3u => Ok(m3u::write(playlist)),
+        FileFormat::Xspf => xspf::write(playlist),
+        FileFormat::Jspf => jspf::write(playlist),
+        FileFormat::Csv => csv::write(playlist),
+        FileFormat::Text => Ok(text::write(playlist)),
+    }
+}
+
+/// Return the YouTube videos contained in a playlist file
+/// * `content` - content of the playlist file
+/// * `format` - format of the playlist file
+/// * `csv_columns` - names of the columns to read from CSV files
+pub fn import(content: &str, format: FileFormat, csv_columns: &csv::Columns) -> Result<Vec<Video>> {
+    match format {
+        FileFormat::M3u => Ok(m3u::read(content)),
+        FileFormat::Xspf => xspf::read(content),
+        FileFormat::Jspf => jspf::read(content),
+        FileFormat::Csv => csv::read(content, csv_columns),
+        FileFormat::Text => text::read(content),
+    }
+}
@@ -0,0 +1,264 @@
+//! CSV files with one video per row
+
+use crate::playlist::{Playlist, Video};
+use crate::prelude::*;
+use crate::{ids, utils};
+
+/// Header of exported CSV files
+const HEADER: [&str; 10] = [
+    "id",
+    "title",
+    "published_at",
+    "url",
+    "duration",
+    "channel_title",
+    "channel_id",
+    "view_count",
+    "like_count",
+    "tags",
+];
+
+/// Separator of multiple tags within the `tags` column
+const TAG_SEPARATOR: &str = ";";
+
+/// Names of the CSV columns to read video fields from
+///
+/// Defaults to the column names used by `write`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub published_at: String,
+    pub duration: String,
+    pub channel_title: String,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self {
+            id: "id".into(),
+            url: "url".into(),
+            title: "title".into(),
+            published_at: "published_at".into(),
+            duration: "duration".into(),
+            channel_title: "channel_title".into(),
+        }
+    }
+}
+
+impl Columns {
+    /// Return the default columns with the given mappings applied
+    /// * `mappings` - list of `<field>=<column>` (e.g. `id=Video ID`)
+    pub fn from_mappings(mappings: &[String]) -> Result<Self> {
+        let mut columns = Self::default();
+
+        for mapping in mappings {
+            let (field, column) = mapping
+                .split_once('=')
+                .ok_or_else(|| Error::InvalidColumnMapping(mapping.to_string()))?;
+            let column = column.trim().to_string();
+
+            match field.trim() {
+                "id" => columns.id = column,
+                "url" => columns.url = column,
+                "title" => columns.title = column,
+                "published_at" => columns.published_at = column,
+                "duration" => columns.duration = column,
+                "channel_title" => columns.channel_title = column,
+                _ => return Err(Error::InvalidColumnMapping(mapping.to_string())),
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Return a CSV representation of `playlist` with a header row
+///
+/// Durations are written in seconds, tags are separated by `;`
+/// * `playlist` - playlist to export
+pub fn write(playlist: &Playlist) -> Result<String> {
+    let mut writer = ::csv::Writer::from_writer(Vec::new());
+    writer.write_record(HEADER)?;
+
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    for video in playlist.videos() {
+        writer.write_record([
+            video.id(),
+            video.title(),
+            video.published_at(),
+            video.url(),
+            &optional(video.duration_seconds()),
+            video.channel_title(),
+            video.channel_id(),
+            &optional(video.view_count()),
+            &optional(video.like_count()),
+            &video.tags().join(TAG_SEPARATOR),
+        ])?;
+    }
+
+    let content = writer
+        .into_inner()
+        .map_err(|error| Error::IO(error.into_error()))?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Return all videos of a CSV file with a header row
+///
+/// The video ID is read from the ID column, falling back to the URL column.
+/// Both accept raw IDs as well as URLs.
+/// Durations are read as seconds or ISO 8601 durations.
+/// Fails with `Error::InvalidVideoIds` listing every row value without a valid ID.
+/// * `content` - content of the CSV file
+/// * `columns` - names of the columns to read
+pub fn read(content: &str, columns: &Columns) -> Result<Vec<Video>> {
+    let mut reader = ::csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let position = |column: &str| headers.iter().position(|header| header.trim() == column);
+
+    let id_position = position(&columns.id);
+    let url_position = position(&columns.url);
+    if id_position.is_none() && url_position.is_none() {
+        return Err(Error::InvalidColumnMapping(format!(
+            "neither `{}` nor `{}` found in header",
+            columns.id, columns.url
+        )));
+    }
+    let title_position = position(&columns.title);
+    let published_at_position = position(&columns.published_at);
+    let duration_position = position(&columns.duration);
+    let channel_title_position = position(&columns.channel_title);
+
+    let mut videos = Vec::new();
+    let mut invalid = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |position: Option<usize>| {
+            position
+                .and_then(|position| record.get(position))
+                .map(str::trim)
+                .unwrap_or_default()
+        };
+
+        let id = [field(id_position), field(url_position)]
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .find_map(ids::parse_video_id);
+        let Some(id) = id else {
+            invalid.push(format!("{}{}", field(id_position), field(url_position)));
+            continue;
+        };
+
+        let duration = field(duration_position);
+        let duration = duration
+            .parse::<u64>()
+            .ok()
+            .or_else(|| utils::parse_iso8601_duration(duration));
+
+        videos.push(
+            Video::from_entry(
+                id,
+                field(title_position),
+                duration,
+                field(channel_title_position),
+            )
+            .with_published_at(field(published_at_position)),
+        );
+    }
+
+    if invalid.is_empty() {
+        Ok(videos)
+    } else {
+        Err(Error::InvalidVideoIds(invalid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(vec![Video::from_entry(
+            "dQw4w9WgXcQ",
+            "Never Gonna, Give You Up",
+            Some(213),
+            "Rick Astley",
+        )
+        .with_published_at("2009-10-25T06:57:33Z")]);
+
+        assert_eq!(
+            write(&playlist)?,
+            "id,title,published_at,url,duration,channel_title,channel_id,view_count,like_count,tags\n\
+             dQw4w9WgXcQ,\"Never Gonna, Give You Up\",2009-10-25T06:57:33Z,https://www.youtube.com/watch?v=dQw4w9WgXcQ,213,Rick Astley,,,,\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_with_mapping() -> Result<()> {
+        let content = "Link,Name,Length\n\
+                       https://youtu.be/dQw4w9WgXcQ,Never Gonna Give You Up,PT3M33S\n\
+                       y6120QOlsfU,Sandstorm,444\n";
+        let columns = Columns::from_mappings(&[
+            "url=Link".into(),
+            "title=Name".into(),
+            "duration= Length".into(),
+        ])?;
+        let videos = read(content, &columns)?;
+
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].id(), "dQw4w9WgXcQ");
+        assert_eq!(videos[0].title(), "Never Gonna Give You Up");
+        assert_eq!(videos[0].duration_seconds(), Some(213));
+        assert_eq!(videos[1].id(), "y6120QOlsfU");
+        assert_eq!(videos[1].duration_seconds(), Some(444));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        assert!(matches!(
+            read("id\ndQw4w9WgXcQ\nfoo\n", &Columns::default()),
+            Err(Error::InvalidVideoIds(invalid)) if invalid == vec!["foo".to_string()]
+        ));
+        assert!(matches!(
+            read("name\nfoo\n", &Columns::default()),
+            Err(Error::InvalidColumnMapping(_))
+        ));
+        assert!(matches!(
+            Columns::from_mappings(&["foo=bar".into()]),
+            Err(Error::InvalidColumnMapping(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(vec![
+            Video::from_entry(
+                "dQw4w9WgXcQ",
+                "Never Gonna Give You Up",
+                Some(213),
+                "Rick Astley",
+            )
+            .with_published_at("2009-10-25T06:57:33Z"),
+            Video::from_entry("y6120QOlsfU", "", None, ""),
+        ]);
+        let videos = read(&write(&playlist)?, &Columns::default())?;
+
+        assert_eq!(videos, playlist.videos());
+        for (video, expected) in videos.iter().zip(playlist.videos()) {
+            assert_eq!(video.title(), expected.title());
+            assert_eq!(video.published_at(), expected.published_at());
+            assert_eq!(video.channel_title(), expected.channel_title());
+            assert_eq!(video.duration_seconds(), expected.duration_seconds());
+        }
+
+        Ok(())
+    }
+}
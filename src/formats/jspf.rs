@@ -0,0 +1,140 @@
+//! JSON Shareable Playlist Format (JSPF)
+//!
+//! See <https://xspf.org/jspf>
+
+use super::Track;
+use crate::playlist::{Playlist, Video};
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the top level JSPF object
+#[derive(Debug, Deserialize, Serialize)]
+struct Jspf {
+    playlist: JspfPlaylist,
+}
+
+/// Data structure for a JSPF playlist
+#[derive(Debug, Deserialize, Serialize)]
+struct JspfPlaylist {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default)]
+    track: Vec<Track>,
+}
+
+/// Return a JSPF representation of `playlist`
+/// * `playlist` - playlist to export
+pub fn write(playlist: &Playlist) -> Result<String> {
+    let jspf = Jspf {
+        playlist: JspfPlaylist {
+            title: Some(playlist.title().to_string()),
+            track: playlist.videos().iter().map(Track::from).collect(),
+        },
+    };
+
+    Ok(serde_json::to_string_pretty(&jspf)? + "\n")
+}
+
+/// Return all YouTube videos of a JSPF playlist
+///
+/// Tracks without a YouTube location are skipped.
+/// * `content` - content of the JSPF file
+pub fn read(content: &str) -> Result<Vec<Video>> {
+    let jspf: Jspf = serde_json::from_str(content)?;
+
+    Ok(jspf
+        .playlist
+        .track
+        .into_iter()
+        .filter_map(Track::into_video)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let content = r#"{
+            "playlist": {
+                "title": "test",
+                "creator": "ignored",
+                "track": [
+                    {
+                        "location": ["https://www.youtube.com/watch?v=dQw4w9WgXcQ"],
+                        "title": "Never Gonna Give You Up",
+                        "creator": "Rick Astley",
+                        "duration": 213000
+                    },
+                    {
+                        "location": ["file:///music/song.mp3"]
+                    }
+                ]
+            }
+        }"#;
+        let videos = read(content)?;
+
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].id(), "dQw4w9WgXcQ");
+        assert_eq!(videos[0].title(), "Never Gonna Give You Up");
+        assert_eq!(videos[0].channel_title(), "Rick Astley");
+        assert_eq!(videos[0].duration_seconds(), Some(213));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(vec![
+            Video::from_entry(
+                "dQw4w9WgXcQ",
+                "Never Gonna Give You Up",
+                Some(213),
+                "Rick Astley",
+            ),
+            Video::from_entry("y6120QOlsfU", "", None, ""),
+        ]);
+        let content = write(&playlist)?;
+        let videos = read(&content)?;
+
+        assert!(content.contains(r#""location": ["#));
+        assert_eq!(videos, playlist.videos());
+        for (video, expected) in videos.iter().zip(playlist.videos()) {
+            assert_eq!(video.title(), expected.title());
+            assert_eq!(video.channel_title(), expected.channel_title());
+            assert_eq!(video.duration_seconds(), expected.duration_seconds());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_huge_duration() -> Result<()> {
+        let playlist = Playlist::from_json(&format!(
+            r#"{{
+                "schemaVersion": 1,
+                "title": "test",
+                "numItems": 1,
+                "videos": [{{
+                    "id": "dQw4w9WgXcQ",
+                    "title": "",
+                    "publishedAt": "",
+                    "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                    "duration": "PT{}S"
+                }}],
+                "urls": []
+            }}"#,
+            u64::MAX
+        ))?;
+        assert_eq!(playlist.videos()[0].duration_seconds(), Some(u64::MAX));
+
+        let content = write(&playlist)?;
+
+        assert!(!content.contains("duration"));
+        assert_eq!(read(&content)?[0].duration_seconds(), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+//! Extended M3U playlists
+//!
+//! Every video is written as an `#EXTINF:<seconds>,<title>` line followed by its watch URL.
+
+use crate::ids;
+use crate::playlist::{Playlist, Video};
+
+const HEADER: &str = "#EXTM3U";
+const PLAYLIST_TAG: &str = "#PLAYLIST:";
+const EXTINF_TAG: &str = "#EXTINF:";
+
+/// Return an extended M3U representation of `playlist`
+///
+/// Unknown durations are written as `-1`, missing titles are replaced by the video ID
+/// * `playlist` - playlist to export
+pub fn write(playlist: &Playlist) -> String {
+    let mut lines = vec![
+        HEADER.to_string(),
+        format!("{}{}", PLAYLIST_TAG, single_line(playlist.title())),
+    ];
+
+    for video in playlist.videos() {
+        let duration = video
+            .duration_seconds()
+            .map(|seconds| seconds.to_string())
+            .unwrap_or_else(|| "-1".into());
+        let title = match video.title() {
+            "" => video.id(),
+            title => title,
+        };
+
+        lines.push(format!("{}{},{}", EXTINF_TAG, duration, single_line(title)));
+        lines.push(video.url().to_string());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Return all YouTube videos of an (extended) M3U playlist
+///
+/// Titles and durations are taken from preceding `#EXTINF` lines.
+/// Entries that aren't YouTube URLs are skipped.
+/// * `content` - content of the M3U file
+pub fn read(content: &str) -> Vec<Video> {
+    let mut videos = Vec::new();
+    let mut extinf: Option<(Option<u64>, String)> = None;
+
+    for line in content.lines().map(str::trim) {
+        if let Some(info) = line.strip_prefix(EXTINF_TAG) {
+            extinf = Some(parse_extinf(info));
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else {
+            let (duration, title) = extinf.take().unwrap_or_default();
+            if let Some(id) = ids::parse_video_id(line) {
+                let title = if title == id { String::new() } else { title };
+                videos.push(Video::from_entry(id, title, duration, ""));
+            }
+        }
+    }
+
+    videos
+}
+
+/// Return duration and title of an `#EXTINF` line without its tag
+///
+/// The duration may be followed by attributes (e.g. `-1 tvg-id="..."`), which are ignored.
+/// * `info` - e.g. `213,Rick Astley - Never Gonna Give You Up`
+fn parse_extinf(info: &str) -> (Option<u64>, String) {
+    let (attributes, title) = info.split_once(',').unwrap_or((info, ""));
+    let duration = attributes
+        .split_whitespace()
+        .next()
+        .and_then(|duration| duration.parse::<u64>().ok());
+
+    (duration, title.trim().to_string())
+}
+
+/// Replace line breaks, which would corrupt the line based format
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_playlist() -> Playlist {
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(vec![
+            Video::from_entry(
+                "dQw4w9WgXcQ",
+                "Rick Astley - Never Gonna Give You Up",
+                Some(213),
+                "Rick Astley",
+            ),
+            Video::from_entry("y6120QOlsfU", "", None, ""),
+        ]);
+        playlist
+    }
+
+    #[test]
+    fn test_write() {
+        assert_eq!(
+            write(&test_playlist()),
+            "#EXTM3U\n\
+             #PLAYLIST:test\n\
+             #EXTINF:213,Rick Astley - Never Gonna Give You Up\n\
+             https://www.youtube.com/watch?v=dQw4w9WgXcQ\n\
+             #EXTINF:-1,y6120QOlsfU\n\
+             https://www.youtube.com/watch?v=y6120QOlsfU\n"
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let content = "#EXTM3U\n\
+                       #EXTINF:213 tvg-id=\"1\",Rick Astley - Never Gonna Give You Up\n\
+                       https://youtu.be/dQw4w9WgXcQ?t=42\n\
+                       \n\
+                       # just a comment\n\
+                       /home/user/music/song.mp3\n\
+                       https://www.youtube.com/watch?v=y6120QOlsfU\n";
+        let videos = read(content);
+
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].id(), "dQw4w9WgXcQ");
+        assert_eq!(videos[0].title(), "Rick Astley - Never Gonna Give You Up");
+        assert_eq!(videos[0].duration_seconds(), Some(213));
+        assert_eq!(videos[1].id(), "y6120QOlsfU");
+        assert_eq!(videos[1].title(), "");
+        assert_eq!(videos[1].duration_seconds(), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let playlist = test_playlist();
+        let videos = read(&write(&playlist));
+
+        assert_eq!(videos, playlist.videos());
+        for (video, expected) in videos.iter().zip(playlist.videos()) {
+            assert_eq!(video.title(), expected.title());
+            assert_eq!(video.duration_seconds(), expected.duration_seconds());
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Plain text files with one video per line
+
+use crate::ids;
+use crate::playlist::{Playlist, Video};
+use crate::prelude::*;
+
+/// Return the URLs of all videos in `playlist`, one per line
+/// * `playlist` - playlist to export
+pub fn write(playlist: &Playlist) -> String {
+    playlist
+        .videos()
+        .iter()
+        .map(|video| format!("{}\n", video.url()))
+        .collect()
+}
+
+/// Return all videos of a plain text file with one ID or URL per line
+///
+/// IDs may also be separated by whitespace or commas, blank lines and `#` comments are ignored.
+/// See `ids::split_id_list`.
+/// Fails with `Error::InvalidVideoIds` listing every token without a valid ID.
+/// * `content` - content of the text file
+pub fn read(content: &str) -> Result<Vec<Video>> {
+    Ok(ids::parse_video_ids(&ids::split_id_list(content))?
+        .into_iter()
+        .map(Video::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let content = "# favourites\n\
+                       https://youtu.be/dQw4w9WgXcQ\n\
+                       \n\
+                       y6120QOlsfU\n";
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(read(content)?);
+
+        let content = write(&playlist);
+        assert_eq!(
+            content,
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ\n\
+             https://www.youtube.com/watch?v=y6120QOlsfU\n"
+        );
+        assert_eq!(read(&content)?, playlist.videos());
+
+        assert!(matches!(
+            read("dQw4w9WgXcQ\nfoo"),
+            Err(Error::InvalidVideoIds(invalid)) if invalid == vec!["foo".to_string()]
+        ));
+
+        Ok(())
+    }
+}
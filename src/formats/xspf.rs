@@ -0,0 +1,150 @@
+//! XML Shareable Playlist Format (XSPF)
+//!
+//! See <https://xspf.org/spec>
+
+use super::Track;
+use crate::playlist::{Playlist, Video};
+use crate::prelude::*;
+use quick_xml::se::Serializer;
+use serde::{Deserialize, Serialize};
+
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+const XSPF_NAMESPACE: &str = "http://xspf.org/ns/0/";
+
+/// Data structure for the root `<playlist>` element
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename = "playlist")]
+struct XspfPlaylist {
+    #[serde(rename = "@version", default)]
+    version: String,
+    #[serde(rename = "@xmlns", default)]
+    xmlns: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(rename = "trackList", default)]
+    track_list: TrackList,
+}
+
+/// Data structure for the `<trackList>` element
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct TrackList {
+    #[serde(rename = "track", default)]
+    tracks: Vec<Track>,
+}
+
+/// Return an XSPF representation of `playlist`
+/// * `playlist` - playlist to export
+pub fn write(playlist: &Playlist) -> Result<String> {
+    let xspf = XspfPlaylist {
+        version: "1".into(),
+        xmlns: XSPF_NAMESPACE.into(),
+        title: Some(playlist.title().to_string()),
+        track_list: TrackList {
+            tracks: playlist.videos().iter().map(Track::from).collect(),
+        },
+    };
+
+    let mut xml = String::new();
+    let mut serializer = Serializer::new(&mut xml);
+    serializer.indent(' ', 2);
+    xspf.serialize(serializer)?;
+
+    Ok(format!("{}\n{}\n", XML_DECLARATION, xml))
+}
+
+/// Return all YouTube videos of an XSPF playlist
+///
+/// Tracks without a YouTube location are skipped.
+/// * `content` - content of the XSPF file
+pub fn read(content: &str) -> Result<Vec<Video>> {
+    let xspf: XspfPlaylist = quick_xml::de::from_str(content)?;
+
+    Ok(xspf
+        .track_list
+        .tracks
+        .into_iter()
+        .filter_map(Track::into_video)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() -> Result<()> {
+        let mut playlist = Playlist::new("Rock & Roll");
+        playlist.push_videos(vec![Video::from_entry(
+            "dQw4w9WgXcQ",
+            "Never Gonna Give You Up",
+            Some(213),
+            "Rick Astley",
+        )]);
+
+        assert_eq!(
+            write(&playlist)?,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Rock &amp; Roll</title>
+  <trackList>
+    <track>
+      <location>https://www.youtube.com/watch?v=dQw4w9WgXcQ</location>
+      <title>Never Gonna Give You Up</title>
+      <creator>Rick Astley</creator>
+      <duration>213000</duration>
+    </track>
+  </trackList>
+</playlist>
+"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <playlist version="1" xmlns="http://xspf.org/ns/0/">
+              <trackList>
+                <track>
+                  <location>file:///music/song.mp3</location>
+                  <location>https://youtu.be/dQw4w9WgXcQ</location>
+                  <title>Never Gonna Give You Up</title>
+                  <annotation>ignored</annotation>
+                  <duration>213000</duration>
+                </track>
+                <track>
+                  <location>file:///music/other.mp3</location>
+                </track>
+              </trackList>
+            </playlist>"#;
+        let videos = read(content)?;
+
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].id(), "dQw4w9WgXcQ");
+        assert_eq!(videos[0].title(), "Never Gonna Give You Up");
+        assert_eq!(videos[0].channel_title(), "");
+        assert_eq!(videos[0].duration_seconds(), Some(213));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.push_videos(vec![
+            Video::from_entry("dQw4w9WgXcQ", "<Never> \"Gonna\"", Some(213), "Rick Astley"),
+            Video::from_entry("y6120QOlsfU", "", None, ""),
+        ]);
+        let videos = read(&write(&playlist)?)?;
+
+        assert_eq!(videos, playlist.videos());
+        for (video, expected) in videos.iter().zip(playlist.videos()) {
+            assert_eq!(video.title(), expected.title());
+            assert_eq!(video.channel_title(), expected.channel_title());
+            assert_eq!(video.duration_seconds(), expected.duration_seconds());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,381 @@
+//! Per-playlist history of past states, used to undo changes
+//!
+//! Before a command changes a playlist, the previous state is recorded as a `Snapshot`.
+//! The snapshots of a playlist are stored in `.history/<file name of the playlist>` inside the save directory,
+//! most recent first, and pruned according to `Retention` whenever a new one is recorded.
+
+use crate::config::Config;
+use crate::playlist::Playlist;
+use crate::prelude::*;
+use crate::schema::PlaylistFile;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the directory inside the save directory containing all history files
+const HISTORY_DIRECTORY: &str = ".history";
+
+/// Limits for the number and age of snapshots kept per playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Maximum number of snapshots, unlimited if `None`
+    pub max_entries: Option<usize>,
+    /// Maximum age of snapshots, unlimited if `None`
+    pub max_age: Option<Duration>,
+}
+
+impl From<&Config> for Retention {
+    fn from(value: &Config) -> Self {
+        Self {
+            max_entries: (value.history_max_entries > 0).then_some(value.history_max_entries),
+            // Ages too large to represent are as good as unlimited
+            max_age: (value.history_max_age_days > 0)
+                .then(|| value.history_max_age_days.checked_mul(24 * 60 * 60))
+                .flatten()
+                .map(Duration::from_secs),
+        }
+    }
+}
+
+/// State of a playlist before a change
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+    /// Command that changed the playlist, e.g. "add"
+    operation: String,
+    /// Content of the playlist file, migrated on restore like any other playlist file
+    playlist: Value,
+}
+
+impl Snapshot {
+    /// Capture the current state of `playlist`
+    /// * `playlist` - playlist about to be changed
+    /// * `operation` - command changing the playlist, e.g. "add"
+    pub fn new(playlist: &Playlist, operation: impl Into<String>) -> Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        Ok(Self {
+            timestamp,
+            operation: operation.into(),
+            playlist: serde_json::to_value(PlaylistFile::from(playlist))?,
+        })
+    }
+
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.timestamp)
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Return the captured playlist
+    pub fn playlist(&self) -> Result<Playlist> {
+        Playlist::from_json(&self.playlist.to_string())
+    }
+
+    /// Return `true` if `playlist` is still in the captured state
+    fn matches(&self, playlist: &Playlist) -> Result<bool> {
+        Ok(serde_json::to_value(PlaylistFile::from(playlist))? == self.playlist)
+    }
+}
+
+/// Content of a history file
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryFile {
+    /// Title of the playlist the snapshots belong to
+    title: String,
+    entries: Vec<Snapshot>,
+}
+
+/// History of a single playlist
+#[derive(Debug)]
+pub struct History {
+    file_path: PathBuf,
+    title: String,
+    /// Most recent first
+    entries: Vec<Snapshot>,
+}
+
+impl History {
+    /// Load the history of an existing playlist
+    ///
+    /// The history is empty if none was recorded yet, or if the history file belongs to a
+    /// different playlist that was saved under the same file name before.
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn load(playlist_title: &str, file_path: &str) -> Result<History> {
+        let file_path = History::file_path(playlist_title, file_path)?;
+
+        let entries = match fs::read_to_string(&file_path) {
+            Ok(history_json) => {
+                let history: HistoryFile = serde_json::from_str(&history_json)?;
+                match history.title == playlist_title {
+                    true => history.entries,
+                    false => Vec::new(),
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            file_path,
+            title: playlist_title.to_string(),
+            entries,
+        })
+    }
+
+    /// Record `snapshot` in the history of the playlist it was taken of, unless `playlist` didn't change since
+    /// * `snapshot` - state of the playlist before the change
+    /// * `playlist` - state of the playlist after the change
+    /// * `retention` - limits applied to the history
+    /// * `file_path` - path to the save directory
+    pub fn record(
+        snapshot: Snapshot,
+        playlist: &Playlist,
+        retention: &Retention,
+        file_path: &str,
+    ) -> Result<()> {
+        if snapshot.matches(playlist)? {
+            return Ok(());
+        }
+
+        let mut history = History::load(playlist.title(), file_path)?;
+        history.push(snapshot, retention, SystemTime::now());
+        history.save()
+    }
+
+    /// Delete the history of a playlist, if any
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn delete(playlist_title: &str, file_path: &str) -> Result<()> {
+        match fs::remove_file(History::file_path(playlist_title, file_path)?) {
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => Ok(result?),
+        }
+    }
+
+    /// Return the path of the history file of an existing playlist
+    fn file_path(playlist_title: &str, file_path: &str) -> Result<PathBuf> {
+        let playlist_file_path = Playlist::find_file_path(playlist_title, file_path)?
+            .ok_or_else(|| Error::PlaylistNotFound(playlist_title.to_string()))?;
+        let directory = utils::expand_path_aliases(PathBuf::from(file_path));
+        let file_name = playlist_file_path
+            .file_name()
+            .ok_or_else(|| Error::StringFromPathBuf(format!("{:?}", playlist_file_path)))?;
+
+        Ok(directory.join(HISTORY_DIRECTORY).join(file_name))
+    }
+
+    /// Return all snapshots, most recent first
+    pub fn entries(&self) -> &[Snapshot] {
+        &self.entries
+    }
+
+    /// Return the snapshot with the given number
+    /// * `number` - 1-based position in `entries`, 1 is the most recent snapshot
+    pub fn get(&self, number: usize) -> Result<&Snapshot> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+            .ok_or(Error::InvalidHistoryEntry(number))
+    }
+
+    /// Remove and return the most recent snapshot
+    pub fn pop(&mut self) -> Result<Snapshot> {
+        match self.entries.is_empty() {
+            true => Err(Error::EmptyHistory(self.title.to_string())),
+            false => Ok(self.entries.remove(0)),
+        }
+    }
+
+    /// Add `snapshot` as the most recent snapshot and prune the history
+    /// * `snapshot` - state of the playlist before a change
+    /// * `retention` - limits applied to the history
+    /// * `now` - current time, used to determine the age of snapshots
+    pub fn push(&mut self, snapshot: Snapshot, retention: &Retention, now: SystemTime) {
+        self.entries.insert(0, snapshot);
+
+        if let Some(max_age) = retention.max_age {
+            self.entries.retain(|snapshot| {
+                now.duration_since(snapshot.time())
+                    .is_ok_and(|age| age <= max_age)
+                    // Keep snapshots from the future, e.g. after the clock was adjusted
+                    || snapshot.time() > now
+            });
+        }
+        if let Some(max_entries) = retention.max_entries {
+            self.entries.truncate(max_entries);
+        }
+    }
+
+    /// Write the history to its file
+    pub fn save(&self) -> Result<()> {
+        if let Some(directory) = self.file_path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let history_json = serde_json::to_string(&HistoryFile {
+            title: self.title.to_string(),
+            entries: self.entries.to_vec(),
+        })?;
+        utils::write_atomic(&self.file_path, history_json)
+    }
+
+    /// Attach the history to a renamed playlist and save it
+    ///
+    /// The playlist has to be saved under its new title already.
+    /// * `playlist_title` - new name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn move_to(mut self, playlist_title: &str, file_path: &str) -> Result<()> {
+        let old_file_path = self.file_path;
+        self.file_path = History::file_path(playlist_title, file_path)?;
+        self.title = playlist_title.to_string();
+        self.save()?;
+
+        if old_file_path != self.file_path {
+            match fs::remove_file(old_file_path) {
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                result => result?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp: u64, operation: &str) -> Snapshot {
+        Snapshot {
+            timestamp,
+            operation: operation.into(),
+            playlist: Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_retention_from_config() {
+        let retention = |history_max_entries, history_max_age_days| {
+            Retention::from(&Config {
+                history_max_entries,
+                history_max_age_days,
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(
+            retention(10, 2),
+            Retention {
+                max_entries: Some(10),
+                max_age: Some(Duration::from_secs(2 * 24 * 60 * 60)),
+            }
+        );
+        assert_eq!(
+            retention(0, 0),
+            Retention {
+                max_entries: None,
+                max_age: None,
+            }
+        );
+        assert_eq!(retention(1, u64::MAX).max_age, None);
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut history = History {
+            file_path: PathBuf::new(),
+            title: "music".into(),
+            entries: Vec::new(),
+        };
+        let now = UNIX_EPOCH + Duration::from_secs(100);
+        let retention = Retention {
+            max_entries: Some(3),
+            max_age: Some(Duration::from_secs(50)),
+        };
+
+        for (timestamp, operation) in [(10, "add"), (60, "remove"), (70, "add"), (80, "rename")] {
+            history.push(snapshot(timestamp, operation), &retention, now);
+        }
+        let operations = history
+            .entries()
+            .iter()
+            .map(Snapshot::operation)
+            .collect::<Vec<&str>>();
+        assert_eq!(operations, vec!["rename", "add", "remove"]);
+
+        history.push(snapshot(90, "add"), &retention, now);
+        assert_eq!(history.entries().len(), 3);
+        assert_eq!(history.get(1).map(Snapshot::operation).ok(), Some("add"));
+        assert!(matches!(history.get(0), Err(Error::InvalidHistoryEntry(0))));
+        assert!(matches!(history.get(4), Err(Error::InvalidHistoryEntry(4))));
+
+        let unlimited = Retention {
+            max_entries: None,
+            max_age: None,
+        };
+        history.push(snapshot(0, "add"), &unlimited, now);
+        assert_eq!(history.entries().len(), 4);
+    }
+
+    #[test]
+    fn test_history_files() -> Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("oxysound-test-history-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let directory = directory.to_str().expect("Temp dir is valid Unicode");
+        let retention = Retention {
+            max_entries: Some(10),
+            max_age: None,
+        };
+
+        let mut playlist = Playlist::new("music");
+        playlist.save_playlist(directory)?;
+        assert!(History::load("music", directory)?.entries().is_empty());
+
+        let snapshot = Snapshot::new(&playlist, "add")?;
+        History::record(snapshot.clone(), &playlist, &retention, directory)?;
+        assert!(History::load("music", directory)?.entries().is_empty());
+
+        playlist.add_videos(&["dQw4w9WgXcQ".into()]);
+        History::record(snapshot, &playlist, &retention, directory)?;
+        playlist.save_playlist(directory)?;
+        let history = History::load("music", directory)?;
+        assert_eq!(history.entries().len(), 1);
+        assert_eq!(history.get(1)?.playlist()?, Playlist::new("music"));
+
+        playlist.set_title("renamed");
+        playlist.save_playlist(directory)?;
+        Playlist::delete_playlist("music", directory)?;
+        history.move_to("renamed", directory)?;
+        let mut history = History::load("renamed", directory)?;
+        assert_eq!(history.pop()?.operation(), "add");
+        assert!(matches!(history.pop(), Err(Error::EmptyHistory(_))));
+
+        // A new playlist saved under the old file name doesn't inherit the history
+        Playlist::new("music").save_playlist(directory)?;
+        fs::copy(
+            format!("{}/.history/renamed.json", directory),
+            format!("{}/.history/music.json", directory),
+        )?;
+        assert!(History::load("music", directory)?.entries().is_empty());
+
+        History::delete("renamed", directory)?;
+        assert!(History::load("renamed", directory)?.entries().is_empty());
+
+        fs::remove_dir_all(directory)?;
+        Ok(())
+    }
+}
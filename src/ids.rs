@@ -0,0 +1,290 @@
+//! Extracts and validates YouTube IDs from user input
+
+use crate::prelude::*;
+
+/// Number of characters in a YouTube video ID
+const VIDEO_ID_LENGTH: usize = 11;
+
+/// Range of characters in a YouTube playlist ID
+const MIN_PLAYLIST_ID_LENGTH: usize = 13;
+const MAX_PLAYLIST_ID_LENGTH: usize = 64;
+
+/// Return `true` if `id` has the shape of a YouTube video ID
+///
+/// A video ID consists of exactly 11 characters out of `[A-Za-z0-9_-]`
+pub fn is_valid_video_id(id: &str) -> bool {
+    id.len() == VIDEO_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Return the video ID contained in `input`, if any
+///
+/// Accepts raw IDs as well as all common URL shapes, with or without scheme:
+/// - `youtube.com/watch?v=<ID>` (additional query parameters like `&t=42` are ignored)
+/// - `youtu.be/<ID>`
+/// - `youtube.com/shorts/<ID>`, `youtube.com/embed/<ID>`, `youtube.com/live/<ID>`, `youtube.com/v/<ID>`
+/// - `www.`, `m.` and `music.` subdomains as well as `youtube-nocookie.com`
+/// * `input` - raw ID or URL
+pub fn parse_video_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    if is_valid_video_id(input) {
+        return Some(input.to_string());
+    }
+
+    let (host, path, query) = split_url(input)?;
+    let candidate = match host.as_str() {
+        "youtu.be" => path.split('/').next(),
+        "youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = path.split('/');
+            match segments.next() {
+                Some("watch") => query_param(query, "v"),
+                Some("shorts" | "embed" | "live" | "v" | "e") => segments.next(),
+                _ => None,
+            }
+        }
+        _ => None,
+    }?;
+
+    is_valid_video_id(candidate).then(|| candidate.to_string())
+}
+
+/// Return the video IDs contained in `inputs`, keeping their order
+///
+/// Fails with `Error::InvalidVideoIds` listing every token that couldn't be parsed
+/// * `inputs` - list of raw IDs and/or URLs
+pub fn parse_video_ids(inputs: &[String]) -> Result<Vec<String>> {
+    let mut ids = Vec::with_capacity(inputs.len());
+    let mut invalid = Vec::new();
+
+    for input in inputs {
+        match parse_video_id(input) {
+            Some(id) => ids.push(id),
+            None => invalid.push(input.to_string()),
+        }
+    }
+
+    if invalid.is_empty() {
+        Ok(ids)
+    } else {
+        Err(Error::InvalidVideoIds(invalid))
+    }
+}
+
+/// Split a list of IDs and/or URLs into single tokens
+///
+/// Tokens are separated by whitespace, line breaks or commas.
+/// Blank lines and comments (starting with `#` at the beginning of a line or after whitespace) are ignored.
+/// * `text` - e.g. the content of a file or `stdin`
+pub fn split_id_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(strip_comment)
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Return `line` without a trailing comment
+///
+/// A `#` only starts a comment at the beginning of the line or after whitespace,
+/// so URL fragments like `watch?v=<ID>#t=42` are kept
+fn strip_comment(line: &str) -> &str {
+    let mut previous = None;
+    for (index, c) in line.char_indices() {
+        if c == '#' && !matches!(previous, Some(previous) if !char::is_whitespace(previous)) {
+            return &line[..index];
+        }
+        previous = Some(c);
+    }
+    line
+}
+
+/// Return the playlist ID contained in `input`, if any
+///
+/// Accepts raw playlist IDs (e.g. `PL...`) as well as YouTube URLs with a `list` query parameter,
+/// e.g. `youtube.com/playlist?list=<ID>` or `youtube.com/watch?v=<VIDEO_ID>&list=<ID>`
+/// * `input` - raw playlist ID or URL
+pub fn parse_playlist_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    if is_valid_playlist_id(input) {
+        return Some(input.to_string());
+    }
+
+    let (host, _, query) = split_url(input)?;
+    let candidate = match host.as_str() {
+        "youtube.com" | "youtu.be" => query_param(query, "list"),
+        _ => None,
+    }?;
+
+    is_valid_playlist_id(candidate).then(|| candidate.to_string())
+}
+
+/// Return `true` if `id` has the shape of a YouTube playlist ID
+///
+/// Playlist IDs vary in length (e.g. `PL` followed by 16 or 32 characters) and consist of `[A-Za-z0-9_-]`
+fn is_valid_playlist_id(id: &str) -> bool {
+    (MIN_PLAYLIST_ID_LENGTH..=MAX_PLAYLIST_ID_LENGTH).contains(&id.len())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Split a URL into its normalized host, path and query
+///
+/// The scheme, fragment and the `www.`, `m.` and `music.` subdomains are stripped.
+/// The path is returned without its leading `/`.
+/// * `url` - URL with or without scheme
+fn split_url(url: &str) -> Option<(String, &str, &str)> {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split('#').next().unwrap_or_default();
+
+    let (host, rest) = url.split_once('/')?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let host = host.to_ascii_lowercase();
+    let host = ["www.", "m.", "music."]
+        .iter()
+        .find_map(|prefix| host.strip_prefix(prefix))
+        .unwrap_or(&host)
+        .to_string();
+
+    Some((host, path, query))
+}
+
+/// Return the value of the query parameter `key`
+/// * `query` - query string without leading `?`
+/// * `key` - name of the parameter
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "dQw4w9WgXcQ";
+
+    #[test]
+    fn test_is_valid_video_id() {
+        assert!(is_valid_video_id(ID));
+        assert!(is_valid_video_id("a-b_c-d_e-f"));
+        assert!(!is_valid_video_id("dQw4w9WgXc"));
+        assert!(!is_valid_video_id("dQw4w9WgXcQQ"));
+        assert!(!is_valid_video_id("dQw4w9WgX?Q"));
+        assert!(!is_valid_video_id(""));
+    }
+
+    #[test]
+    fn test_parse_video_id() {
+        let inputs = [
+            "dQw4w9WgXcQ",
+            " dQw4w9WgXcQ\n",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42",
+            "https://www.youtube.com/watch?feature=share&v=dQw4w9WgXcQ",
+            "http://youtube.com/watch?v=dQw4w9WgXcQ#comments",
+            "www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=RDAMVM",
+            "https://youtu.be/dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ?t=42",
+            "youtu.be/dQw4w9WgXcQ",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ?autoplay=1",
+            "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ",
+            "https://www.youtube.com/live/dQw4w9WgXcQ",
+            "https://www.youtube.com/v/dQw4w9WgXcQ",
+            "HTTPS://WWW.YOUTUBE.COM/watch?v=dQw4w9WgXcQ",
+            "youtube.com/watch?v=dQw4w9WgXcQ",
+        ];
+        for input in inputs {
+            assert_eq!(parse_video_id(input).as_deref(), Some(ID), "{input}");
+        }
+
+        let invalid = [
+            "",
+            "id_1",
+            "https://www.youtube.com/watch?v=dQw4w9WgXc",
+            "https://www.youtube.com/watch?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "https://example.com/watch?v=dQw4w9WgXcQ",
+            "https://youtu.be/",
+        ];
+        for input in invalid {
+            assert_eq!(parse_video_id(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_video_ids() {
+        let ids = parse_video_ids(&["https://youtu.be/dQw4w9WgXcQ".into(), "y6120QOlsfU".into()])
+            .expect("All inputs are valid");
+        assert_eq!(ids, vec!["dQw4w9WgXcQ".to_string(), "y6120QOlsfU".into()]);
+
+        match parse_video_ids(&["dQw4w9WgXcQ".into(), "foo".into(), "bar".into()]) {
+            Err(Error::InvalidVideoIds(invalid)) => {
+                assert_eq!(invalid, vec!["foo".to_string(), "bar".into()])
+            }
+            other => panic!("Expected `Error::InvalidVideoIds`, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_playlist_id() {
+        const LIST_ID: &str = "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG";
+
+        let inputs = [
+            "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+            "https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG&index=2",
+            "https://music.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+            "https://youtu.be/dQw4w9WgXcQ?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+        ];
+        for input in inputs {
+            assert_eq!(
+                parse_playlist_id(input).as_deref(),
+                Some(LIST_ID),
+                "{input}"
+            );
+        }
+
+        let invalid = [
+            "",
+            "dQw4w9WgXcQ",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://www.youtube.com/playlist?list=PL?x",
+            "https://example.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+        ];
+        for input in invalid {
+            assert_eq!(parse_playlist_id(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_split_id_list() {
+        let text = "# exported IDs\n\
+                    dQw4w9WgXcQ y6120QOlsfU,https://youtu.be/dQw4w9WgXcQ\n\
+                    \n\
+                    \t https://www.youtube.com/watch?v=dQw4w9WgXcQ#t=42  # with timestamp\n\
+                    a-b_c-d_e-f,\n";
+
+        assert_eq!(
+            split_id_list(text),
+            vec![
+                "dQw4w9WgXcQ".to_string(),
+                "y6120QOlsfU".into(),
+                "https://youtu.be/dQw4w9WgXcQ".into(),
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ#t=42".into(),
+                "a-b_c-d_e-f".into(),
+            ]
+        );
+        assert!(split_id_list("").is_empty());
+        assert!(split_id_list("#only a comment").is_empty());
+    }
+}
@@ -1,145 +1,1417 @@
 //! Main crate logic
 
-use std::fs::read_dir;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, read_dir};
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::time::SystemTime;
 
-use crate::args::{Arguments, Operation};
+use crate::args::{Arguments, FileFormat, ListSort, Operation, SetArgs};
+use crate::config::Config;
+use crate::history::{History, Retention, Snapshot};
+use crate::output::{
+    DeleteOutput, Duplicate, HistoryEntry, Occurrence, Output, RefreshOutput, SearchMatch,
+    SearchResultEntry,
+};
 use crate::playlist::Playlist;
 use crate::prelude::*;
+use crate::provider::MetadataProvider;
+use crate::search::MatchMode;
+use crate::template::Template;
+use crate::youtube_api::YouTubeProvider;
 
 pub mod args;
 pub mod config;
 mod error;
+mod formats;
+mod history;
+mod ids;
+mod output;
 mod playlist;
 mod prelude;
+mod provider;
+mod schema;
+mod search;
+mod template;
 mod utils;
 mod youtube_api;
 
+pub use output::{print_error, ErrorKind};
+
 /// Run the application
 ///
 /// * `args` - parsed CLI arguments
-/// * `save_directory` - location to look for saved playlists and save playlist after applying changes
-pub async fn run(args: Arguments, save_directory: impl Into<String>) -> Result<()> {
-    let save_directory = save_directory.into();
+/// * `config` - loaded config, e.g. containing the location to look for saved playlists and save playlist after applying changes
+pub async fn run(args: Arguments, config: &Config) -> Result<()> {
+    let save_directory = config.save_directory.to_string();
+    let provider = YouTubeProvider::new(config);
+    let output = Output::new(args.output);
+    let retention = Retention::from(config);
+
+    for playlist_title in playlist_titles(&args.operation) {
+        for warning in Playlist::invalid_files(playlist_title, &save_directory)? {
+            output.warning(warning);
+        }
+    }
 
     match args.operation {
-        Operation::Add(args) => add(args.playlist_title, args.ids, &save_directory).await?,
-        Operation::Remove(args) => remove(args.playlist_title, args.ids, &save_directory)?,
-        Operation::Print(args) => print(args.playlist_title, args.ids, &save_directory)?,
-        Operation::List => {
-            list(&save_directory)?;
+        Operation::Create(args) => create(args.playlist_title, &save_directory, &output)?,
+        Operation::Add(args) => {
+            let ids = read_ids(args.ids, args.ids_file)?;
+            add(
+                args.playlist_title,
+                ids,
+                args.create,
+                &save_directory,
+                &retention,
+                &provider,
+                &output,
+            )
+            .await?
+        }
+        Operation::Remove(args) => {
+            let ids = read_ids(args.ids, args.ids_file)?;
+            remove(
+                args.playlist_title,
+                ids,
+                &save_directory,
+                &retention,
+                &output,
+            )?
+        }
+        Operation::Print(args) => {
+            let ids = match (args.ids, args.ids_file) {
+                (None, None) => None,
+                (ids, ids_file) => Some(read_ids(ids.unwrap_or_default(), ids_file)?),
+            };
+            let output = output.with_template(resolve_template(args.format, config)?);
+            print(args.playlist_title, ids, &save_directory, &output)?
+        }
+        Operation::List(args) => {
+            let output = output.with_template(resolve_template(args.format, config)?);
+            list(&save_directory, args.sort, &output)?;
+        }
+        Operation::Refresh(args) => {
+            refresh(
+                args.playlist_title,
+                args.all,
+                &save_directory,
+                &provider,
+                &output,
+            )
+            .await?
+        }
+        Operation::Import(args) => {
+            let source = match (args.list, args.file) {
+                (Some(list), None) => ImportSource::List(list),
+                (None, Some(file)) => ImportSource::File(file, args.format, args.columns),
+                _ => unreachable!("Unreachable because `ImportArgs.list` and `ImportArgs.file` are mutually exclusive"),
+            };
+            import(
+                args.playlist_title,
+                source,
+                args.merge,
+                &save_directory,
+                &retention,
+                &provider,
+                &output,
+            )
+            .await?
+        }
+        Operation::Export(args) => {
+            export(args.playlist_title, args.format, args.file, &save_directory)?
         }
+        Operation::Rename(args) => copy(
+            args.playlist_title,
+            args.new_title,
+            args.force,
+            true,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Copy(args) => copy(
+            args.playlist_title,
+            args.new_title,
+            args.force,
+            false,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Delete(args) => delete(args.playlist_title, args.yes, &save_directory, &output)?,
+        Operation::Union(args) => combine(
+            args,
+            SetOperation::Union,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Intersect(args) => combine(
+            args,
+            SetOperation::Intersect,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Difference(args) => combine(
+            args,
+            SetOperation::Difference,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Which(args) => match args.video {
+            Some(video) => which(video, &save_directory, &output)?,
+            None => dupes(&save_directory, &output)?,
+        },
+        Operation::SearchLocal(args) => {
+            let mode = match args.fuzzy {
+                true => MatchMode::Fuzzy,
+                false => MatchMode::Substring,
+            };
+            search_local(&args.query, mode, &save_directory, &output)?
+        }
+        Operation::Search(args) => {
+            search(
+                &args.query,
+                usize::from(args.limit),
+                args.add_to
+                    .map(|playlist_title| (playlist_title, args.select)),
+                &save_directory,
+                &retention,
+                &provider,
+                &output,
+            )
+            .await?
+        }
+        Operation::History(args) => history(args.playlist_title, &save_directory, &output)?,
+        Operation::Undo(args) => restore(
+            args.playlist_title,
+            None,
+            &save_directory,
+            &retention,
+            &output,
+        )?,
+        Operation::Restore(args) => restore(
+            args.playlist_title,
+            Some(args.number),
+            &save_directory,
+            &retention,
+            &output,
+        )?,
     };
 
     Ok(())
 }
 
-/// Add videos to playlist
+/// Return the titles of all playlists `operation` looks up or saves
+/// * `operation` - parsed CLI operation
+fn playlist_titles(operation: &Operation) -> Vec<&str> {
+    match operation {
+        Operation::Create(args) => vec![&args.playlist_title],
+        Operation::Add(args) => vec![&args.playlist_title],
+        Operation::Remove(args) => vec![&args.playlist_title],
+        Operation::Print(args) => args.playlist_title.iter().map(String::as_str).collect(),
+        Operation::Refresh(args) => args.playlist_title.iter().map(String::as_str).collect(),
+        Operation::Import(args) => vec![&args.playlist_title],
+        Operation::Export(args) => vec![&args.playlist_title],
+        Operation::Rename(args) | Operation::Copy(args) => {
+            vec![&args.playlist_title, &args.new_title]
+        }
+        Operation::Delete(args) => vec![&args.playlist_title],
+        Operation::Union(args) | Operation::Intersect(args) | Operation::Difference(args) => args
+            .playlist_titles
+            .iter()
+            .chain([&args.new_title])
+            .map(String::as_str)
+            .collect(),
+        Operation::List(_) | Operation::Which(_) | Operation::SearchLocal(_) => Vec::new(),
+        Operation::Search(args) => args.add_to.iter().map(String::as_str).collect(),
+        Operation::History(args) | Operation::Undo(args) => vec![&args.playlist_title],
+        Operation::Restore(args) => vec![&args.playlist_title],
+    }
+}
+
+/// Return all video ID and URL tokens from the command line, `stdin` and a file
 ///
-/// If a file_path is provided, videos are added to existing playlist.
-/// Otherwise a new playlist containing the videos is created.
+/// An `ids` entry of `-` is replaced by the list read from `stdin`.
+/// See `ids::split_id_list` for the accepted list format.
+///
+/// * `ids` - tokens passed on the command line
+/// * `ids_file` - path to a file containing a list of IDs
+fn read_ids(ids: Vec<String>, ids_file: Option<PathBuf>) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+
+    for id in ids {
+        let list = match id.as_str() {
+            "-" => io::read_to_string(io::stdin())?,
+            _ => id,
+        };
+        tokens.append(&mut ids::split_id_list(&list));
+    }
+    if let Some(ids_file) = ids_file {
+        tokens.append(&mut ids::split_id_list(&fs::read_to_string(ids_file)?));
+    }
+
+    Ok(tokens)
+}
+
+/// Return the template selected via `--format`
+///
+/// `format` is either the name of a template saved in the config or a template itself.
+/// See `template` for the available fields.
+/// * `format` - value of `--format`
+/// * `config` - loaded config containing the saved templates
+fn resolve_template(format: Option<String>, config: &Config) -> Result<Option<Template>> {
+    format
+        .map(|format| {
+            config
+                .templates
+                .get(&format)
+                .map_or(format.as_str(), String::as_str)
+                .parse()
+        })
+        .transpose()
+}
+
+/// Create a new, empty playlist
+///
+/// Fails if the playlist already exists.
 ///
 /// * `playlist_title` - name of the playlist
-/// * `ids` - list of video IDs
+/// * `file_directory` - location to save the new playlist
+/// * `output` - printer for the new playlist
+fn create(playlist_title: String, file_path: impl Into<String>, output: &Output) -> Result<()> {
+    let file_path = file_path.into();
+
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    if Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistAlreadyExists(playlist_title));
+    }
+    let playlist = Playlist::new(&playlist_title);
+    playlist.save_playlist(&file_path)?;
+
+    output.playlist(&playlist)
+}
+
+/// Add videos to an existing playlist
+///
+/// Fails if the playlist doesn't exist, unless `create` is set.
+/// In that case a new playlist containing the videos is created.
+///
+/// * `playlist_title` - name of the playlist
+/// * `ids` - list of video IDs or URLs
+/// * `create` - create the playlist if it doesn't exist
 /// * `file_directory` - location to look for existing playlist or save new playlist
-async fn add(playlist_title: String, ids: Vec<String>, file_path: impl Into<String>) -> Result<()> {
+/// * `retention` - limits of the playlist's history
+/// * `provider` - source of video meta data
+/// * `output` - printer for the resulting playlist
+async fn add(
+    playlist_title: String,
+    ids: Vec<String>,
+    create: bool,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    provider: &impl MetadataProvider,
+    output: &Output,
+) -> Result<()> {
     let file_path = file_path.into();
+    let ids = ids::parse_video_ids(&ids)?;
 
-    let mut playlist = match Playlist::load_playlist(&playlist_title, &file_path)? {
-        Some(playlist) => playlist,
-        None => Playlist::new(&playlist_title),
+    if !create && !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    let (mut playlist, snapshot) = match Playlist::load_playlist(&playlist_title, &file_path)? {
+        Some(playlist) => {
+            let snapshot = Snapshot::new(&playlist, "add")?;
+            (playlist, Some(snapshot))
+        }
+        None if create => (Playlist::new(&playlist_title), None),
+        None => return Err(Error::PlaylistNotFound(playlist_title)),
     };
     playlist.add_videos(&ids);
-    playlist.fetch_metadata().await?;
+    let report = playlist.fetch_metadata(provider).await?;
 
-    println!("{}", playlist);
+    output.playlist(&playlist)?;
+    if !report.unavailable.is_empty() {
+        output.message(format!("\n{}", report));
+    }
+    if let Some(snapshot) = snapshot {
+        History::record(snapshot, &playlist, retention, &file_path)?;
+    }
     playlist.save_playlist(&file_path)?;
 
     Ok(())
 }
 
-/// Remove videos from playlist
+/// Remove videos from an existing playlist
 ///
-/// If a file_path is provided, videos are removed from existing playlist.
-/// Otherwise a new playlist containing the videos is created.
-/// The latter operation functionally does nothing and merely allows for simplifications.
+/// Fails if the playlist doesn't exist.
 ///
 /// * `playlist_title` - name of the playlist
-/// * `ids` - list of video IDs
-/// * `file_directory` - location to look for existing playlist or save new playlist
-fn remove(playlist_title: String, ids: Vec<String>, file_path: impl Into<String>) -> Result<()> {
+/// * `ids` - list of video IDs or URLs
+/// * `file_directory` - location to look for existing playlist
+/// * `retention` - limits of the playlist's history
+/// * `output` - printer for the resulting playlist
+fn remove(
+    playlist_title: String,
+    ids: Vec<String>,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    output: &Output,
+) -> Result<()> {
     let file_path = file_path.into();
+    let ids = ids::parse_video_ids(&ids)?;
 
-    let mut playlist = match Playlist::load_playlist(&playlist_title, &file_path)? {
-        Some(playlist) => playlist,
-        None => Playlist::new(&playlist_title),
-    };
+    // Checked before locking, so a typo in the title doesn't leave a lock file behind
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    let mut playlist = Playlist::load_existing(&playlist_title, &file_path)?;
+    let snapshot = Snapshot::new(&playlist, "remove")?;
     playlist.remove_videos(&ids);
 
-    println!("{}", playlist);
+    output.playlist(&playlist)?;
+    History::record(snapshot, &playlist, retention, &file_path)?;
     playlist.save_playlist(&file_path)?;
 
     Ok(())
 }
 
-/// Print playlist URL to `stdout`
+/// Print playlist (including its URL) to `stdout`
 ///
-/// If a file_path is provided, existing playlist is used.
+/// If a title is provided, the existing playlist is used, failing if it doesn't exist.
 /// Otherwise a new playlist containing the videos is used.
 /// These arguments have to be mutually exclusive.
 ///
 /// * `playlist_title` - name of the playlist
-/// * `ids` - list of video IDs
+/// * `ids` - list of video IDs or URLs
 /// * `file_directory` - location to look for existing playlist or save new playlist
+/// * `output` - printer for the playlist
 fn print(
     playlist_title: Option<String>,
     ids: Option<Vec<String>>,
     file_path: impl Into<String>,
+    output: &Output,
 ) -> Result<()> {
     let file_path = file_path.into();
 
     let playlist = match (playlist_title, ids) {
-        (Some(playlist_title), None) => {
-            match Playlist::load_playlist(&playlist_title, file_path)? {
-                Some(playlist) => playlist,
-                None => Playlist::new(&playlist_title),
-            }
-        }
+        (Some(playlist_title), None) => Playlist::load_existing(&playlist_title, file_path)?,
         (None, Some(ids)) => {
+            let ids = ids::parse_video_ids(&ids)?;
             let mut playlist = Playlist::default();
             playlist.add_videos(&ids);
             playlist
         }
-        _ => unreachable!("Unreachable because `PrintArgs.playlist_title` and `PrintArgs.ids` are mutually exclusive"),
+        _ => unreachable!("Unreachable because `PrintArgs.playlist_title` and `PrintArgs.ids`/`PrintArgs.ids_file` are mutually exclusive"),
     };
 
-    println!("{}", playlist);
+    output.playlist(&playlist)?;
 
     Ok(())
 }
 
-/// Print a list of all available playlists
+/// Re-fetch meta data of existing playlists and print a report of all changes
+///
+/// A playlist is only saved if something changed.
+/// These arguments have to be mutually exclusive.
+///
+/// * `playlist_title` - name of the playlist
+/// * `all` - refresh all playlists in `file_directory`
+/// * `file_directory` - location to look for existing playlists
+/// * `provider` - source of video meta data
+/// * `output` - printer for the report
+async fn refresh(
+    playlist_title: Option<String>,
+    all: bool,
+    file_path: impl Into<String>,
+    provider: &impl MetadataProvider,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    let playlist_titles = match (playlist_title, all) {
+        (Some(playlist_title), false) => {
+            if !Playlist::exists(&playlist_title, &file_path)? {
+                return Err(Error::PlaylistNotFound(playlist_title));
+            }
+            vec![playlist_title]
+        }
+        (None, true) => saved_playlists(&file_path, output)?
+            .into_iter()
+            .map(|(playlist, _)| playlist.title().to_string())
+            .collect(),
+        _ => unreachable!("Unreachable because `RefreshArgs.playlist_title` and `RefreshArgs.all` are mutually exclusive"),
+    };
+
+    let mut reports = Vec::new();
+    for playlist_title in playlist_titles {
+        let _lock = Playlist::lock(&playlist_title, &file_path)?;
+        // Loaded again while locked, skipped if it was deleted in the meantime
+        let Some(mut playlist) = Playlist::load_playlist(&playlist_title, &file_path)? else {
+            continue;
+        };
+        let changes = playlist.refresh_metadata(provider).await?;
+
+        if !changes.is_empty() {
+            playlist.save_playlist(&file_path)?;
+        }
+        reports.push(RefreshOutput {
+            title: playlist_title,
+            changes,
+        });
+    }
+
+    output.values(&reports)
+}
+
+/// Source of videos to import
+enum ImportSource {
+    /// ID or URL of a YouTube playlist
+    List(String),
+    /// Path to a playlist file, its format, if specified, and CSV column mappings
+    File(PathBuf, Option<FileFormat>, Vec<String>),
+}
+
+/// Import an existing YouTube playlist or a playlist file
+///
+/// Fails if the playlist already exists, unless `merge` is set.
+/// In that case new videos are appended to the existing playlist.
+///
+/// * `playlist_title` - name of the playlist
+/// * `source` - YouTube playlist or playlist file to import
+/// * `merge` - merge into an existing playlist
+/// * `file_directory` - location to look for existing playlist or save new playlist
+/// * `retention` - limits of the playlist's history
+/// * `provider` - source of video meta data
+/// * `output` - printer for the resulting playlist
+async fn import(
+    playlist_title: String,
+    source: ImportSource,
+    merge: bool,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    provider: &impl MetadataProvider,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    let (mut playlist, snapshot) = match Playlist::load_playlist(&playlist_title, &file_path)? {
+        Some(playlist) if merge => {
+            let snapshot = Snapshot::new(&playlist, "import")?;
+            (playlist, Some(snapshot))
+        }
+        Some(_) => return Err(Error::PlaylistAlreadyExists(playlist_title)),
+        None => (Playlist::new(&playlist_title), None),
+    };
+
+    let report = match source {
+        ImportSource::List(list) => {
+            let playlist_id =
+                ids::parse_playlist_id(&list).ok_or(Error::InvalidPlaylistId(list))?;
+            playlist.import_videos(&playlist_id, provider).await?
+        }
+        ImportSource::File(import_path, format, columns) => {
+            let format = format
+                .or_else(|| FileFormat::from_path(&import_path))
+                .ok_or_else(|| Error::UnknownFileFormat(format!("{:?}", import_path)))?;
+            let csv_columns = formats::csv::Columns::from_mappings(&columns)?;
+            let content = fs::read_to_string(&import_path)?;
+            let videos = formats::import(&content, format, &csv_columns)?;
+            // Same validation as for IDs passed to `add`, duplicates are skipped by `push_videos`
+            let video_ids: Vec<String> =
+                videos.iter().map(|video| video.id().to_string()).collect();
+            ids::parse_video_ids(&video_ids)?;
+            playlist.push_videos(videos);
+            playlist.fetch_metadata(provider).await?
+        }
+    };
+
+    output.playlist(&playlist)?;
+    if !report.unavailable.is_empty() {
+        output.message(format!("\n{}", report));
+    }
+    if let Some(snapshot) = snapshot {
+        History::record(snapshot, &playlist, retention, &file_path)?;
+    }
+    playlist.save_playlist(&file_path)?;
+
+    Ok(())
+}
+
+/// Export an existing playlist to a playlist file
+///
+/// * `playlist_title` - name of the playlist
+/// * `format` - format of the playlist file
+/// * `export_path` - path of the playlist file, print to `stdout` if `None`
+/// * `file_directory` - location to look for existing playlist
+fn export(
+    playlist_title: String,
+    format: FileFormat,
+    export_path: Option<PathBuf>,
+    file_path: impl Into<String>,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    let playlist = Playlist::load_existing(&playlist_title, &file_path)?;
+    let content = formats::export(&playlist, format)?;
+
+    match export_path {
+        Some(export_path) => fs::write(export_path, content)?,
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// Copy or rename a playlist
+///
+/// A renamed playlist keeps its history, a copy starts without history.
+/// If an existing playlist is overwritten (`force`), it keeps its own history instead
+/// and its previous state is recorded, so `undo` brings it back.
+///
+/// * `playlist_title` - name of the existing playlist
+/// * `new_title` - name of the copy
+/// * `force` - overwrite an existing playlist named `new_title`
+/// * `remove_source` - delete the existing playlist afterwards (i.e. rename it)
+/// * `file_directory` - location to look for existing playlists
+/// * `retention` - limits of the playlist's history
+/// * `output` - printer for the resulting playlist
+fn copy(
+    playlist_title: String,
+    new_title: String,
+    force: bool,
+    remove_source: bool,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    if new_title == playlist_title {
+        return Err(Error::PlaylistAlreadyExists(new_title));
+    }
+    let _locks = Playlist::lock_all(&[&playlist_title, &new_title], &file_path)?;
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    let replaced = Playlist::load_playlist(&new_title, &file_path)?;
+    if !force && replaced.is_some() {
+        return Err(Error::PlaylistAlreadyExists(new_title));
+    }
+
+    let operation = match remove_source {
+        true => "rename",
+        false => "copy",
+    };
+    let mut playlist = Playlist::load_existing(&playlist_title, &file_path)?;
+    let history = match (remove_source, &replaced) {
+        (true, None) => Some((
+            History::load(&playlist_title, &file_path)?,
+            Snapshot::new(&playlist, operation)?,
+        )),
+        _ => None,
+    };
+    playlist.set_title(&new_title);
+    if let Some(replaced) = replaced {
+        History::record(
+            Snapshot::new(&replaced, operation)?,
+            &playlist,
+            retention,
+            &file_path,
+        )?;
+    }
+    playlist.save_playlist(&file_path)?;
+    if remove_source {
+        match history {
+            Some((mut history, snapshot)) => {
+                Playlist::delete_playlist(&playlist_title, &file_path)?;
+                history.push(snapshot, retention, SystemTime::now());
+                history.move_to(&new_title, &file_path)?;
+            }
+            None => {
+                History::delete(&playlist_title, &file_path)?;
+                Playlist::delete_playlist(&playlist_title, &file_path)?;
+            }
+        }
+    }
+
+    output.playlist(&playlist)
+}
+
+/// Delete a playlist and its history after asking the user for confirmation
+///
+/// * `playlist_title` - name of the playlist
+/// * `yes` - skip the confirmation
+/// * `file_directory` - location to look for existing playlists
+/// * `output` - printer for the result
+fn delete(
+    playlist_title: String,
+    yes: bool,
+    file_path: impl Into<String>,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    let deleted = yes || confirm(&format!("Delete playlist {}?", playlist_title))?;
+    if deleted {
+        History::delete(&playlist_title, &file_path)?;
+        Playlist::delete_playlist(&playlist_title, &file_path)?;
+    }
+
+    output.value(&DeleteOutput {
+        title: playlist_title,
+        deleted,
+    })
+}
+
+/// Ask the user a yes/no question on `stderr` and return `true` if they answered yes
+/// * `question` - question to ask
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Operation combining several playlists
+enum SetOperation {
+    Union,
+    Intersect,
+    Difference,
+}
+
+/// Combine several playlists into a new playlist
+///
+/// Videos keep their meta data and the order of the first playlist.
+///
+/// An overwritten playlist keeps its history and its previous state is recorded, so `undo` brings it back.
+///
+/// * `args` - titles of the playlists, title of the new playlist and whether to overwrite it
+/// * `operation` - how to combine the playlists
+/// * `file_directory` - location to look for existing playlists and save the new playlist
+/// * `retention` - limits of the overwritten playlist's history
+/// * `output` - printer for the resulting playlist
+fn combine(
+    args: SetArgs,
+    operation: SetOperation,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    let _lock = Playlist::lock(&args.new_title, &file_path)?;
+    let replaced = Playlist::load_playlist(&args.new_title, &file_path)?;
+    if !args.force && replaced.is_some() {
+        return Err(Error::PlaylistAlreadyExists(args.new_title));
+    }
+
+    let mut playlists = Vec::with_capacity(args.playlist_titles.len());
+    for playlist_title in args.playlist_titles {
+        playlists.push(Playlist::load_existing(&playlist_title, &file_path)?);
+    }
+
+    let mut playlists = playlists.into_iter();
+    let mut playlist = playlists.next().unwrap_or_default();
+    for other in playlists {
+        match operation {
+            SetOperation::Union => playlist.union(other),
+            SetOperation::Intersect => playlist.intersect(&other),
+            SetOperation::Difference => playlist.difference(&other),
+        }
+    }
+    playlist.set_title(&args.new_title);
+    if let Some(replaced) = replaced {
+        let operation = match operation {
+            SetOperation::Union => "union",
+            SetOperation::Intersect => "intersect",
+            SetOperation::Difference => "difference",
+        };
+        History::record(
+            Snapshot::new(&replaced, operation)?,
+            &playlist,
+            retention,
+            &file_path,
+        )?;
+    }
+    playlist.save_playlist(&file_path)?;
+
+    output.playlist(&playlist)
+}
+
+/// Print every saved playlist containing a video and the video's position
+///
+/// * `video` - video ID or URL
+/// * `file_directory` - location to look for playlists
+/// * `output` - printer for the occurrences
+fn which(video: String, file_path: impl Into<String>, output: &Output) -> Result<()> {
+    let id = ids::parse_video_id(&video).ok_or_else(|| Error::InvalidVideoIds(vec![video]))?;
+
+    let occurrences = saved_playlists(file_path, output)?
+        .into_iter()
+        .filter_map(|(playlist, _)| {
+            playlist.position(&id).map(|position| Occurrence {
+                playlist: playlist.title().to_string(),
+                position: position + 1,
+            })
+        })
+        .collect::<Vec<Occurrence>>();
+
+    match occurrences.is_empty() {
+        true => output.message(format!("{} isn't part of any playlist", id)),
+        false => output.message(format!("Playlists containing {}:", id)),
+    }
+    output.values(&occurrences)
+}
+
+/// Print every video that is part of more than one saved playlist
 ///
 /// * `file_directory` - location to look for playlists
-fn list(file_path: impl Into<String>) -> Result<()> {
+/// * `output` - printer for the duplicates
+fn dupes(file_path: impl Into<String>, output: &Output) -> Result<()> {
+    let mut duplicates: Vec<Duplicate> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+
+    for (playlist, _) in saved_playlists(file_path, output)? {
+        for (position, video) in playlist.videos().iter().enumerate() {
+            let index = *indices.entry(video.id().to_string()).or_insert_with(|| {
+                duplicates.push(Duplicate {
+                    id: video.id().to_string(),
+                    title: video.title().to_string(),
+                    occurrences: Vec::new(),
+                });
+                duplicates.len() - 1
+            });
+            duplicates[index].occurrences.push(Occurrence {
+                playlist: playlist.title().to_string(),
+                position: position + 1,
+            });
+        }
+    }
+    duplicates.retain(|duplicate| duplicate.occurrences.len() > 1);
+
+    if duplicates.is_empty() {
+        output.message("No video is part of more than one playlist");
+    }
+    output.values(&duplicates)
+}
+
+/// Print every saved video whose meta data matches a query
+///
+/// * `query` - text to search for
+/// * `mode` - how to compare the query to the meta data
+/// * `file_directory` - location to look for playlists
+/// * `output` - printer for the matches
+fn search_local(
+    query: &str,
+    mode: MatchMode,
+    file_path: impl Into<String>,
+    output: &Output,
+) -> Result<()> {
+    let playlists = saved_playlists(file_path, output)?;
+
+    let matches = playlists
+        .iter()
+        .flat_map(|(playlist, _)| {
+            playlist
+                .videos()
+                .iter()
+                .enumerate()
+                .filter_map(move |(position, video)| {
+                    search::match_video(video, query, mode).map(|matched_in| SearchMatch {
+                        playlist: playlist.title(),
+                        position: position + 1,
+                        id: video.id(),
+                        title: video.title(),
+                        channel_title: video.channel_title(),
+                        url: video.url(),
+                        matched_in,
+                    })
+                })
+        })
+        .collect::<Vec<SearchMatch>>();
+
+    if matches.is_empty() {
+        output.message(format!("No saved video matches \"{}\"", query));
+    }
+    output.values(&matches)
+}
+
+/// Search YouTube for videos and print the ranked results
+///
+/// Selected results are added to an existing playlist.
+///
+/// * `query` - search terms
+/// * `limit` - maximum number of results
+/// * `add_to` - title of the playlist to add results to and 1-based ranks of the results to add (all if empty)
+/// * `file_directory` - location to look for existing playlist
+/// * `retention` - limits of the playlist's history
+/// * `provider` - source of search results and video meta data
+/// * `output` - printer for the results
+async fn search(
+    query: &str,
+    limit: usize,
+    add_to: Option<(String, Vec<usize>)>,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    provider: &impl MetadataProvider,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
+
+    if let Some((playlist_title, _)) = &add_to {
+        if !Playlist::exists(playlist_title, &file_path)? {
+            return Err(Error::PlaylistNotFound(playlist_title.to_string()));
+        }
+    }
+    let videos = search::search_videos(query, limit, provider).await?;
+    if videos.is_empty() {
+        output.message(format!("No videos found for \"{}\"", query));
+    }
+    output.values(
+        &videos
+            .iter()
+            .enumerate()
+            .map(|(index, video)| SearchResultEntry::new(index + 1, video))
+            .collect::<Vec<SearchResultEntry>>(),
+    )?;
+
+    let Some((playlist_title, ranks)) = add_to else {
+        return Ok(());
+    };
+    let invalid = ranks
+        .iter()
+        .filter(|rank| !(1..=videos.len()).contains(rank))
+        .copied()
+        .collect::<Vec<usize>>();
+    if !invalid.is_empty() {
+        return Err(Error::InvalidSearchSelection(invalid));
+    }
+    let ids = match ranks.is_empty() {
+        true => videos.iter().map(|video| video.id().to_string()).collect(),
+        false => ranks
+            .iter()
+            .map(|rank| videos[rank - 1].id().to_string())
+            .collect::<Vec<String>>(),
+    };
+
+    let _lock = Playlist::lock(&playlist_title, &file_path)?;
+    let mut playlist = Playlist::load_existing(&playlist_title, &file_path)?;
+    let snapshot = Snapshot::new(&playlist, "add")?;
+    let num_items = playlist.num_items();
+    playlist.add_videos(&ids);
+    playlist.fetch_metadata(provider).await?;
+    History::record(snapshot, &playlist, retention, &file_path)?;
+    playlist.save_playlist(&file_path)?;
+
+    output.message(format!(
+        "Added {} video(s) to {}",
+        playlist.num_items() - num_items,
+        playlist_title
+    ));
+
+    Ok(())
+}
+
+/// Print the past states of a playlist, most recent first
+///
+/// * `playlist_title` - name of the playlist
+/// * `file_directory` - location to look for existing playlist
+/// * `output` - printer for the history
+fn history(playlist_title: String, file_path: impl Into<String>, output: &Output) -> Result<()> {
     let file_path = file_path.into();
-    let mut file_path: PathBuf = PathBuf::from(&file_path);
 
-    file_path = utils::expand_path_aliases(file_path);
+    let history = History::load(&playlist_title, &file_path)?;
+    if history.entries().is_empty() {
+        output.message(format!("No history for {}", playlist_title));
+    }
 
-    println!("Available playlists at {:?}:", &file_path);
+    let entries = history
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(index, snapshot)| HistoryEntry::new(index + 1, snapshot))
+        .collect::<Result<Vec<HistoryEntry>>>()?;
+    output.values(&entries)
+}
+
+/// Replace a playlist with one of its past states
+///
+/// Undoing removes the most recent state from the history, so repeated undos go further back.
+/// Restoring a specific state records the current one first, so it can be undone in turn.
+/// If the past state has a different title (i.e. the playlist was renamed since), the rename is reverted as well.
+///
+/// * `playlist_title` - name of the playlist
+/// * `number` - number of the past state as listed by `history`, undo the last change if `None`
+/// * `file_directory` - location to look for existing playlist
+/// * `retention` - limits of the playlist's history
+/// * `output` - printer for the restored playlist
+fn restore(
+    playlist_title: String,
+    number: Option<usize>,
+    file_path: impl Into<String>,
+    retention: &Retention,
+    output: &Output,
+) -> Result<()> {
+    let file_path = file_path.into();
 
+    if !Playlist::exists(&playlist_title, &file_path)? {
+        return Err(Error::PlaylistNotFound(playlist_title));
+    }
+    // The restored title is only known after reading the history, so both titles get locked in a second pass
+    let mut locked_titles = vec![playlist_title.clone()];
+    let (_locks, restored, history) = loop {
+        let locks = Playlist::lock_all(
+            &locked_titles.iter().map(String::as_str).collect::<Vec<_>>(),
+            &file_path,
+        )?;
+        let playlist = Playlist::load_existing(&playlist_title, &file_path)?;
+        let mut history = History::load(&playlist_title, &file_path)?;
+
+        let restored = match number {
+            None => history.pop()?.playlist()?,
+            Some(number) => {
+                let restored = history.get(number)?.playlist()?;
+                history.push(
+                    Snapshot::new(&playlist, "restore")?,
+                    retention,
+                    SystemTime::now(),
+                );
+                restored
+            }
+        };
+
+        if locked_titles.iter().any(|title| title == restored.title()) {
+            break (locks, restored, history);
+        }
+        drop(locks);
+        locked_titles.push(restored.title().to_string());
+    };
+
+    if restored.title() == playlist_title {
+        restored.save_playlist(&file_path)?;
+        history.save()?;
+    } else {
+        if Playlist::exists(restored.title(), &file_path)? {
+            return Err(Error::PlaylistAlreadyExists(restored.title().to_string()));
+        }
+        restored.save_playlist(&file_path)?;
+        Playlist::delete_playlist(&playlist_title, &file_path)?;
+        history.move_to(restored.title(), &file_path)?;
+    }
+
+    output.playlist(&restored)
+}
+
+/// Return all playlists saved in `file_directory` and the time their file was last modified, sorted by title
+///
+/// Only files containing a valid playlist are returned, unreadable playlist files are reported as warnings.
+///
+/// * `file_directory` - location to look for playlists
+/// * `output` - printer for warnings
+fn saved_playlists(
+    file_path: impl Into<String>,
+    output: &Output,
+) -> Result<Vec<(Playlist, SystemTime)>> {
+    let file_path = utils::expand_path_aliases(PathBuf::from(file_path.into()));
+
+    let mut playlists = Vec::new();
     for entry in read_dir(&file_path)?.filter_map(|entry| entry.ok()) {
         let entry = entry.path();
-        let entry = entry
-            .file_stem()
-            .ok_or_else(|| Error::StringFromPathBuf(format!("{:?}", entry)))?
-            .to_str()
-            .map(String::from)
-            .ok_or_else(|| Error::StringFromPathBuf(format!("{:?}", entry)))?;
+        if !entry.is_file() || entry.extension() != Some(OsStr::new("json")) {
+            continue;
+        }
+        let playlist = fs::read_to_string(&entry)
+            .map_err(Error::from)
+            .and_then(|playlist_json| Playlist::from_json(&playlist_json))
+            .and_then(|playlist| Ok((playlist, fs::metadata(&entry)?.modified()?)));
+        match playlist {
+            Ok(playlist) => playlists.push(playlist),
+            Err(error) => output.warning(format!("Skipping {:?}: {}", entry, error)),
+        }
+    }
+    playlists.sort_by(|(a, _), (b, _)| a.title().cmp(b.title()));
+
+    Ok(playlists)
+}
+
+/// Print a list of all available playlists
+///
+/// See `saved_playlists` for the playlists included.
+///
+/// * `file_directory` - location to look for playlists
+/// * `sort` - order of the list entries
+/// * `output` - printer for the list entries
+fn list(file_path: impl Into<String>, sort: ListSort, output: &Output) -> Result<()> {
+    let file_path = file_path.into();
 
-        println!("- {}", entry);
+    output.message(format!(
+        "Available playlists at {:?}:",
+        utils::expand_path_aliases(PathBuf::from(&file_path))
+    ));
+
+    let mut playlists = saved_playlists(&file_path, output)?;
+
+    // Playlists are sorted by name already, so playlists with equal keys keep a stable order
+    match sort {
+        ListSort::Name => {}
+        ListSort::Size => playlists.sort_by_key(|(playlist, _)| Reverse(playlist.num_items())),
+        ListSort::Modified => playlists.sort_by_key(|(_, modified)| Reverse(*modified)),
+        ListSort::Duration => {
+            playlists.sort_by_key(|(playlist, _)| Reverse(playlist.total_duration()))
+        }
     }
 
-    Ok(())
+    output.playlist_entries(&playlists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::OutputFormat;
+    use crate::provider::InMemoryProvider;
+    use clap::ValueEnum;
+
+    /// Empty save directory, removed again when dropped
+    struct SaveDirectory(PathBuf);
+
+    impl SaveDirectory {
+        fn new(name: &str) -> Self {
+            let directory =
+                std::env::temp_dir().join(format!("oxysound-test-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&directory).expect("Temp dir is writable");
+            Self(directory)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().expect("Temp dir is valid Unicode")
+        }
+    }
+
+    impl Drop for SaveDirectory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    const RETENTION: Retention = Retention {
+        max_entries: Some(10),
+        max_age: None,
+    };
+
+    fn ids(playlist_title: &str, directory: &SaveDirectory) -> Vec<String> {
+        Playlist::load_existing(playlist_title, directory.path())
+            .expect("Playlist exists")
+            .videos()
+            .iter()
+            .map(|video| video.id().to_string())
+            .collect()
+    }
+
+    fn operations(playlist_title: &str, directory: &SaveDirectory) -> Vec<String> {
+        History::load(playlist_title, directory.path())
+            .expect("Playlist exists")
+            .entries()
+            .iter()
+            .map(|snapshot| snapshot.operation().to_string())
+            .collect()
+    }
+
+    async fn add_ids(playlist_title: &str, video_ids: &[&str], directory: &SaveDirectory) {
+        let video_ids = video_ids.iter().map(|id| id.to_string()).collect();
+        let output = Output::new(OutputFormat::Json);
+        add(
+            playlist_title.to_string(),
+            video_ids,
+            true,
+            directory.path(),
+            &RETENTION,
+            &InMemoryProvider::default(),
+            &output,
+        )
+        .await
+        .expect("Videos are added");
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let directory = SaveDirectory::new("round-trip");
+        let output = Output::new(OutputFormat::Json);
+        let video_ids = ["dQw4w9WgXcQ", "y6120QOlsfU", "jNQXAC9IVRw"];
+        add_ids("music", &video_ids, &directory).await;
+
+        for format in FileFormat::value_variants() {
+            let export_path = directory.0.join(format!("export-{:?}", format));
+            export(
+                "music".into(),
+                *format,
+                Some(export_path.clone()),
+                directory.path(),
+            )
+            .expect("Playlist is exported");
+
+            let imported_title = format!("imported-{:?}", format);
+            let source = ImportSource::File(export_path.clone(), Some(*format), Vec::new());
+            import(
+                imported_title.clone(),
+                source,
+                false,
+                directory.path(),
+                &RETENTION,
+                &InMemoryProvider::default(),
+                &output,
+            )
+            .await
+            .expect("Playlist is imported");
+            assert_eq!(ids(&imported_title, &directory), video_ids, "{:?}", format);
+
+            // Merging into the exported playlist doesn't add duplicates
+            let source = ImportSource::File(export_path, Some(*format), Vec::new());
+            import(
+                "music".into(),
+                source,
+                true,
+                directory.path(),
+                &RETENTION,
+                &InMemoryProvider::default(),
+                &output,
+            )
+            .await
+            .expect("Playlist is merged");
+            assert_eq!(ids("music", &directory), video_ids, "{:?}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_missing_playlist() {
+        let directory = SaveDirectory::new("refresh-missing");
+        add_ids("music", &["dQw4w9WgXcQ"], &directory).await;
+
+        assert!(matches!(
+            refresh(
+                Some("musik".into()),
+                false,
+                directory.path(),
+                &InMemoryProvider::default(),
+                &Output::new(OutputFormat::Json),
+            )
+            .await,
+            Err(Error::PlaylistNotFound(_))
+        ));
+        assert!(!Playlist::exists("musik", directory.path()).expect("Directory is readable"));
+    }
+
+    #[test]
+    fn test_export_missing_playlist() {
+        let directory = SaveDirectory::new("export-missing");
+        let export_path = directory.0.join("missing.m3u");
+
+        assert!(matches!(
+            export(
+                "missing".into(),
+                FileFormat::M3u,
+                Some(export_path.clone()),
+                directory.path(),
+            ),
+            Err(Error::PlaylistNotFound(_))
+        ));
+        assert!(!export_path.exists());
+        assert_eq!(read_dir(&directory.0).map(Iterator::count).ok(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_undo_and_restore() -> Result<()> {
+        let directory = SaveDirectory::new("undo");
+        let output = Output::new(OutputFormat::Json);
+
+        add_ids("music", &["dQw4w9WgXcQ"], &directory).await;
+        add_ids("music", &["y6120QOlsfU"], &directory).await;
+        add_ids("music", &["y6120QOlsfU"], &directory).await;
+        remove(
+            "music".into(),
+            vec!["dQw4w9WgXcQ".into()],
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert_eq!(ids("music", &directory), ["y6120QOlsfU"]);
+        // Adding a video that is already part of the playlist changes nothing and isn't recorded
+        assert_eq!(operations("music", &directory), ["remove", "add"]);
+
+        restore("music".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ", "y6120QOlsfU"]);
+        assert_eq!(operations("music", &directory), ["add"]);
+
+        restore(
+            "music".into(),
+            Some(1),
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ"]);
+        assert_eq!(operations("music", &directory), ["restore", "add"]);
+
+        // Undoing a restore goes back to the state before it
+        restore("music".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ", "y6120QOlsfU"]);
+        assert_eq!(operations("music", &directory), ["add"]);
+
+        assert!(matches!(
+            restore(
+                "music".into(),
+                Some(2),
+                directory.path(),
+                &RETENTION,
+                &output
+            ),
+            Err(Error::InvalidHistoryEntry(2))
+        ));
+        restore("music".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ"]);
+        assert!(matches!(
+            restore("music".into(), None, directory.path(), &RETENTION, &output),
+            Err(Error::EmptyHistory(_))
+        ));
+        history("music".into(), directory.path(), &output)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_rename() -> Result<()> {
+        let directory = SaveDirectory::new("undo-rename");
+        let output = Output::new(OutputFormat::Json);
+
+        add_ids("music", &["dQw4w9WgXcQ"], &directory).await;
+        add_ids("music", &["y6120QOlsfU"], &directory).await;
+        copy(
+            "music".into(),
+            "renamed".into(),
+            false,
+            true,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert!(!Playlist::exists("music", directory.path())?);
+        assert_eq!(operations("renamed", &directory), ["rename", "add"]);
+
+        restore(
+            "renamed".into(),
+            None,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert!(!Playlist::exists("renamed", directory.path())?);
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ", "y6120QOlsfU"]);
+        assert_eq!(operations("music", &directory), ["add"]);
+
+        restore("music".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("music", &directory), ["dQw4w9WgXcQ"]);
+
+        // Undoing a rename fails if the old title was taken in the meantime
+        copy(
+            "music".into(),
+            "renamed".into(),
+            false,
+            true,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        create("music".into(), directory.path(), &output)?;
+        assert!(matches!(
+            restore(
+                "renamed".into(),
+                None,
+                directory.path(),
+                &RETENTION,
+                &output
+            ),
+            Err(Error::PlaylistAlreadyExists(_))
+        ));
+        assert_eq!(ids("renamed", &directory), ["dQw4w9WgXcQ"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_undo_overwrite() -> Result<()> {
+        let directory = SaveDirectory::new("undo-overwrite");
+        let output = Output::new(OutputFormat::Json);
+
+        add_ids("source", &["dQw4w9WgXcQ"], &directory).await;
+        add_ids("target", &["y6120QOlsfU"], &directory).await;
+        add_ids("target", &["a-b_c-d_e-f"], &directory).await;
+
+        copy(
+            "source".into(),
+            "target".into(),
+            true,
+            false,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert_eq!(ids("target", &directory), ["dQw4w9WgXcQ"]);
+        assert_eq!(operations("target", &directory), ["copy", "add"]);
+        restore("target".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("target", &directory), ["y6120QOlsfU", "a-b_c-d_e-f"]);
+
+        let args = SetArgs {
+            playlist_titles: vec!["source".into(), "target".into()],
+            new_title: "target".into(),
+            force: true,
+        };
+        combine(
+            args,
+            SetOperation::Union,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert_eq!(operations("target", &directory), ["union", "add"]);
+        restore("target".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("target", &directory), ["y6120QOlsfU", "a-b_c-d_e-f"]);
+
+        copy(
+            "source".into(),
+            "target".into(),
+            true,
+            true,
+            directory.path(),
+            &RETENTION,
+            &output,
+        )?;
+        assert!(!Playlist::exists("source", directory.path())?);
+        assert_eq!(operations("target", &directory), ["rename", "add"]);
+        restore("target".into(), None, directory.path(), &RETENTION, &output)?;
+        assert_eq!(ids("target", &directory), ["y6120QOlsfU", "a-b_c-d_e-f"]);
+
+        Ok(())
+    }
 }
@@ -1,26 +1,29 @@
 use clap::Parser;
-use oxysound::{args::Arguments, config::Config};
+use oxysound::args::Arguments;
+use oxysound::{config::Config, print_error, ErrorKind};
 use std::process;
 
 #[tokio::main]
 async fn main() {
+    let args = Arguments::parse();
+    let output_format = args.output;
+
     let config: Config = match confy::load::<Config>("oxysound", "config") {
         Ok(config) => match config.assert_values() {
             Ok(config) => config,
             Err(e) => {
-                eprint!("Config error: {e}");
+                print_error(output_format, ErrorKind::Config, e);
                 process::exit(1);
             }
         },
         Err(e) => {
-            eprint!("Config error: {e}");
+            print_error(output_format, ErrorKind::Config, e);
             process::exit(1);
         }
     };
-    let args = Arguments::parse();
 
-    if let Err(e) = oxysound::run(args, &config.save_directory).await {
-        eprintln!("Application error: {e}");
+    if let Err(e) = oxysound::run(args, &config).await {
+        print_error(output_format, ErrorKind::Application, e);
         process::exit(1);
     };
 }
@@ -0,0 +1,608 @@
+//! Handles everything printed to `stdout`
+//!
+//! In `text` mode values are printed using their `Display` implementation.
+//! In `json` and `ndjson` mode the structures below are serialized instead,
+//! while human readable messages are sent to `stderr`.
+//! A user-defined `Template` replaces the `Display` implementation in `text` mode.
+
+use crate::args::OutputFormat;
+use crate::history::Snapshot;
+use crate::playlist::{Availability, Playlist, Video, VideoChange};
+use crate::search::MatchedField;
+use crate::template::Template;
+use crate::{prelude::*, utils};
+use serde::Serialize;
+use std::fmt::Display;
+use std::time::SystemTime;
+
+/// Printer for the results of an operation
+#[derive(Debug, Clone)]
+pub struct Output {
+    format: OutputFormat,
+    template: Option<Template>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            template: None,
+        }
+    }
+
+    /// Return `self` rendering videos and playlists using `template` in `text` mode
+    /// * `template` - user-defined template, `Display` is used if `None`
+    pub fn with_template(mut self, template: Option<Template>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Print a human readable message
+    ///
+    /// Sent to `stdout` in `text` mode, else to `stderr`
+    pub fn message(&self, message: impl Display) {
+        match self.format {
+            OutputFormat::Text => println!("{}", message),
+            OutputFormat::Json | OutputFormat::Ndjson => eprintln!("{}", message),
+        }
+    }
+
+    /// Print a single value
+    ///
+    /// `json` prints a pretty object, `ndjson` prints a single line
+    pub fn value<T: Serialize + Display>(&self, value: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Text => {
+                println!("{}", value);
+                Ok(())
+            }
+            OutputFormat::Json | OutputFormat::Ndjson => self.json(value),
+        }
+    }
+
+    /// Print a list of values
+    ///
+    /// `json` prints a pretty array, `ndjson` prints one line per value
+    pub fn values<T: Serialize + Display>(&self, values: &[T]) -> Result<()> {
+        match self.format {
+            OutputFormat::Text => values.iter().for_each(|value| println!("{}", value)),
+            OutputFormat::Json => self.json(values)?,
+            OutputFormat::Ndjson => {
+                for value in values {
+                    println!("{}", serde_json::to_string(value)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print a playlist object, see `PlaylistOutput`
+    ///
+    /// In `text` mode a template is rendered once per video
+    pub fn playlist(&self, playlist: &Playlist) -> Result<()> {
+        match (self.format, &self.template) {
+            (OutputFormat::Text, Some(template)) => {
+                for index in 0..playlist.videos().len() {
+                    println!("{}", template.render_playlist_video(playlist, index));
+                }
+                Ok(())
+            }
+            (OutputFormat::Text, None) => {
+                println!("{}", playlist);
+                Ok(())
+            }
+            (OutputFormat::Json | OutputFormat::Ndjson, _) => {
+                self.json(&PlaylistOutput::from(playlist))
+            }
+        }
+    }
+
+    /// Print one list entry per playlist, see `ListEntry`
+    ///
+    /// In `text` mode a template is rendered once per playlist
+    /// * `playlists` - playlists and the time their file was last modified
+    pub fn playlist_entries(&self, playlists: &[(Playlist, SystemTime)]) -> Result<()> {
+        match (self.format, &self.template) {
+            (OutputFormat::Text, Some(template)) => {
+                for (playlist, _) in playlists {
+                    println!("{}", template.render_playlist(playlist));
+                }
+                Ok(())
+            }
+            _ => self.values(
+                &playlists
+                    .iter()
+                    .map(|(playlist, modified)| ListEntry::new(playlist, *modified))
+                    .collect::<Vec<ListEntry>>(),
+            ),
+        }
+    }
+
+    /// Print a warning to `stderr`, independent of the output format
+    pub fn warning(&self, message: impl Display) {
+        eprintln!("Warning: {}", message);
+    }
+
+    /// Print a single value as JSON, pretty in `json` mode and as a single line otherwise
+    fn json<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            _ => println!("{}", serde_json::to_string(value)?),
+        }
+        Ok(())
+    }
+}
+
+/// Playlist object
+///
+/// ```json
+/// {
+///   "title": "music",
+///   "numItems": 1,
+///   "totalDuration": 213,
+///   "urls": ["http://www.youtube.com/watch_videos?video_ids=dQw4w9WgXcQ"],
+///   "videos": [<video object>]
+/// }
+/// ```
+/// `totalDuration` is the sum of all known video durations in seconds.
+/// `urls` holds one URL per part of at most 50 videos.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistOutput<'a> {
+    title: &'a str,
+    num_items: usize,
+    total_duration: u64,
+    urls: &'a [String],
+    videos: Vec<VideoOutput<'a>>,
+}
+
+impl<'a> From<&'a Playlist> for PlaylistOutput<'a> {
+    fn from(value: &'a Playlist) -> Self {
+        Self {
+            title: value.title(),
+            num_items: value.num_items(),
+            total_duration: value.total_duration(),
+            urls: value.urls(),
+            videos: value.videos().iter().map(VideoOutput::from).collect(),
+        }
+    }
+}
+
+/// Video object
+///
+/// ```json
+/// {
+///   "id": "dQw4w9WgXcQ",
+///   "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)",
+///   "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+///   "publishedAt": "2009-10-25T06:57:33Z",
+///   "availability": "available",
+///   "duration": 213,
+///   "channelTitle": "Rick Astley",
+///   "channelId": "UCuAXFkgsw1L7xaCfnd5JJOw",
+///   "viewCount": 1500000000,
+///   "likeCount": 17000000,
+///   "description": "The official video for “Never Gonna Give You Up” by Rick Astley",
+///   "tags": ["rick astley"]
+/// }
+/// ```
+/// `availability` is one of `available`, `unavailable` or `unfetched`.
+/// `duration` (in seconds), `viewCount` and `likeCount` are `null` if unknown.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoOutput<'a> {
+    id: &'a str,
+    title: &'a str,
+    url: &'a str,
+    published_at: &'a str,
+    availability: Availability,
+    duration: Option<u64>,
+    channel_title: &'a str,
+    channel_id: &'a str,
+    view_count: Option<u64>,
+    like_count: Option<u64>,
+    description: &'a str,
+    tags: &'a [String],
+}
+
+impl<'a> From<&'a Video> for VideoOutput<'a> {
+    fn from(value: &'a Video) -> Self {
+        Self {
+            id: value.id(),
+            title: value.title(),
+            url: value.url(),
+            published_at: value.published_at(),
+            availability: value.availability(),
+            duration: value.duration_seconds(),
+            channel_title: value.channel_title(),
+            channel_id: value.channel_id(),
+            view_count: value.view_count(),
+            like_count: value.like_count(),
+            description: value.description(),
+            tags: value.tags(),
+        }
+    }
+}
+
+/// List entry, one per saved playlist
+///
+/// ```json
+/// { "title": "music", "numItems": 12, "totalDuration": 2843, "modified": "2024-05-17 09:41" }
+/// ```
+/// `totalDuration` is given in seconds, `modified` is the last modification of the file in UTC.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEntry<'a> {
+    title: &'a str,
+    num_items: usize,
+    total_duration: u64,
+    modified: String,
+}
+
+impl<'a> ListEntry<'a> {
+    pub fn new(playlist: &'a Playlist, modified: SystemTime) -> Self {
+        Self {
+            title: playlist.title(),
+            num_items: playlist.num_items(),
+            total_duration: playlist.total_duration(),
+            modified: utils::format_timestamp(modified),
+        }
+    }
+}
+
+impl Display for ListEntry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "- {} ({} videos, {}, modified {})",
+            self.title,
+            self.num_items,
+            utils::format_duration(self.total_duration),
+            self.modified
+        )
+    }
+}
+
+/// Result of refreshing a single playlist
+///
+/// ```json
+/// {
+///   "title": "music",
+///   "changes": [
+///     { "kind": "titleChanged", "id": "dQw4w9WgXcQ", "from": "old", "to": "new" },
+///     { "kind": "nowUnavailable", "id": "y6120QOlsfU", "title": "Darude - Sandstorm" },
+///     { "kind": "nowAvailable", "id": "y6120QOlsfU", "title": "Darude - Sandstorm" },
+///     { "kind": "statisticsChanged", "id": "dQw4w9WgXcQ", "title": "new", "viewCount": [1, 2], "likeCount": [1, 1] },
+///     { "kind": "metadataChanged", "id": "dQw4w9WgXcQ", "title": "new", "fields": ["duration", "tags"] }
+///   ]
+/// }
+/// ```
+/// Statistics are given as `[before, after]`, `fields` lists the names of other changed fields as stored in the playlist file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshOutput {
+    pub title: String,
+    pub changes: Vec<VideoChange>,
+}
+
+impl Display for RefreshOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "{}: no changes", self.title);
+        }
+
+        write!(f, "{}: {} change(s)", self.title, self.changes.len())?;
+        for change in &self.changes {
+            write!(f, "\n\t- {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of deleting a playlist
+///
+/// ```json
+/// { "title": "music", "deleted": true }
+/// ```
+/// `deleted` is `false` if the user didn't confirm.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteOutput {
+    pub title: String,
+    pub deleted: bool,
+}
+
+impl Display for DeleteOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.deleted {
+            true => write!(f, "Deleted playlist {}", self.title),
+            false => write!(f, "Kept playlist {}", self.title),
+        }
+    }
+}
+
+/// Past state of a playlist listed by `history`
+///
+/// ```json
+/// { "number": 1, "timestamp": "2024-05-17 09:41", "operation": "add", "title": "music", "numItems": 12 }
+/// ```
+/// `timestamp` is the time of the change in UTC, `operation` the command that changed the state,
+/// `title` and `numItems` describe the state before the change.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry<'a> {
+    number: usize,
+    timestamp: String,
+    operation: &'a str,
+    title: String,
+    num_items: usize,
+}
+
+impl<'a> HistoryEntry<'a> {
+    pub fn new(number: usize, snapshot: &'a Snapshot) -> Result<Self> {
+        let playlist = snapshot.playlist()?;
+        Ok(Self {
+            number,
+            timestamp: utils::format_timestamp(snapshot.time()),
+            operation: snapshot.operation(),
+            title: playlist.title().to_string(),
+            num_items: playlist.num_items(),
+        })
+    }
+}
+
+impl Display for HistoryEntry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}. {} before {}: {} ({} videos)",
+            self.number, self.timestamp, self.operation, self.title, self.num_items
+        )
+    }
+}
+
+/// Position of a video in a saved playlist
+///
+/// ```json
+/// { "playlist": "music", "position": 3 }
+/// ```
+/// `position` starts at 1.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Occurrence {
+    pub playlist: String,
+    pub position: usize,
+}
+
+impl Display for Occurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "- {} (position {})", self.playlist, self.position)
+    }
+}
+
+/// Video that is part of more than one playlist
+///
+/// ```json
+/// { "id": "dQw4w9WgXcQ", "title": "Never Gonna Give You Up", "occurrences": [<occurrence>] }
+/// ```
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Duplicate {
+    pub id: String,
+    pub title: String,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl Display for Duplicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.title, self.id)?;
+        for occurrence in &self.occurrences {
+            write!(f, "\n\t{}", occurrence)?;
+        }
+        Ok(())
+    }
+}
+
+/// Video matching a local search
+///
+/// ```json
+/// {
+///   "playlist": "music",
+///   "position": 3,
+///   "id": "dQw4w9WgXcQ",
+///   "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)",
+///   "channelTitle": "Rick Astley",
+///   "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+///   "matchedIn": "title"
+/// }
+/// ```
+/// `matchedIn` is one of `title`, `channelTitle`, `description` or `tags`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch<'a> {
+    pub playlist: &'a str,
+    pub position: usize,
+    pub id: &'a str,
+    pub title: &'a str,
+    pub channel_title: &'a str,
+    pub url: &'a str,
+    pub matched_in: MatchedField,
+}
+
+impl Display for SearchMatch<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({})\n\tPlaylist: {} (position {})\n\tMatched in: {}\n\tURL: {}",
+            self.title, self.channel_title, self.playlist, self.position, self.matched_in, self.url
+        )
+    }
+}
+
+/// Result of a YouTube search
+///
+/// ```json
+/// {
+///   "rank": 1,
+///   "id": "dQw4w9WgXcQ",
+///   "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)",
+///   "channelTitle": "Rick Astley",
+///   "duration": 213,
+///   "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+/// }
+/// ```
+/// `rank` starts at 1, `duration` (in seconds) is `null` if unknown.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultEntry<'a> {
+    rank: usize,
+    id: &'a str,
+    title: &'a str,
+    channel_title: &'a str,
+    duration: Option<u64>,
+    url: &'a str,
+}
+
+impl<'a> SearchResultEntry<'a> {
+    /// * `rank` - 1-based position in the search results
+    /// * `video` - video found
+    pub fn new(rank: usize, video: &'a Video) -> Self {
+        Self {
+            rank,
+            id: video.id(),
+            title: video.title(),
+            channel_title: video.channel_title(),
+            duration: video.duration_seconds(),
+            url: video.url(),
+        }
+    }
+}
+
+impl Display for SearchResultEntry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let duration = self
+            .duration
+            .map(utils::format_duration)
+            .unwrap_or_else(|| "unknown".into());
+        write!(
+            f,
+            "{}. {} - {} [{}]\n\t{}",
+            self.rank, self.title, self.channel_title, duration, self.url
+        )
+    }
+}
+
+/// Kind of error that ended the application
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// The config couldn't be loaded or is incomplete
+    Config,
+    /// An operation failed
+    Application,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Config => write!(f, "Config error"),
+            ErrorKind::Application => write!(f, "Application error"),
+        }
+    }
+}
+
+/// Error object, printed instead of the regular output if the application fails
+///
+/// ```json
+/// { "error": { "kind": "application", "message": "Invalid video IDs or URLs: foo" } }
+/// ```
+/// `kind` is either `config` or `application`.
+#[derive(Debug, Serialize)]
+struct ErrorOutput {
+    error: ErrorDetails,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetails {
+    kind: ErrorKind,
+    message: String,
+}
+
+/// Print an error either in human readable form to `stderr` or as error object to `stdout`
+/// * `format` - selected output format
+/// * `kind` - kind of the error
+/// * `message` - human readable error message
+pub fn print_error(format: OutputFormat, kind: ErrorKind, message: impl Display) {
+    match format {
+        OutputFormat::Text => eprintln!("{}: {}", kind, message),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let error = ErrorOutput {
+                error: ErrorDetails {
+                    kind,
+                    message: message.to_string(),
+                },
+            };
+            // Fall back to `stderr` if the error can't be serialized
+            if Output::new(format).json(&error).is_err() {
+                eprintln!("{}: {}", kind, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_output() {
+        let output = RefreshOutput {
+            title: "music".into(),
+            changes: vec![
+                VideoChange::TitleChanged {
+                    id: "dQw4w9WgXcQ".into(),
+                    from: "old".into(),
+                    to: "new".into(),
+                },
+                VideoChange::MetadataChanged {
+                    id: "dQw4w9WgXcQ".into(),
+                    title: "new".into(),
+                    fields: vec!["duration".into(), "tags".into()],
+                },
+            ],
+        };
+
+        assert_eq!(
+            serde_json::to_value(&output).expect("Output is serializable"),
+            serde_json::json!({
+                "title": "music",
+                "changes": [
+                    { "kind": "titleChanged", "id": "dQw4w9WgXcQ", "from": "old", "to": "new" },
+                    { "kind": "metadataChanged", "id": "dQw4w9WgXcQ", "title": "new", "fields": ["duration", "tags"] }
+                ]
+            })
+        );
+        assert_eq!(output.to_string().lines().count(), 3);
+
+        let output = RefreshOutput {
+            title: "music".into(),
+            changes: vec![],
+        };
+        assert_eq!(output.to_string(), "music: no changes");
+    }
+
+    #[test]
+    fn test_error_output() {
+        let error = ErrorOutput {
+            error: ErrorDetails {
+                kind: ErrorKind::Application,
+                message: "Invalid video IDs or URLs: foo".into(),
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_string(&error).expect("Error is serializable"),
+            r#"{"error":{"kind":"application","message":"Invalid video IDs or URLs: foo"}}"#
+        );
+    }
+}
@@ -1,12 +1,29 @@
 //! Playlist API
 
-use crate::error::Error;
-use crate::youtube_api::{self, ResponseItem};
+use crate::provider::MetadataProvider;
+use crate::schema::{self, PlaylistFile};
+use crate::template::Template;
+use crate::youtube_api::ResponseItem;
 use crate::{prelude::*, utils};
+use fs4::fs_std::FileExt;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::fs::{self};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Availability of a video's meta data
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Availability {
+    /// Meta data was fetched successfully
+    Available,
+    /// The API returned no data (e.g. deleted, private or mistyped ID)
+    Unavailable,
+    /// Meta data hasn't been requested yet
+    #[default]
+    Unfetched,
+}
 
 /// Data structure for video meta data
 #[derive(Debug, Deserialize, Serialize)]
@@ -16,7 +33,27 @@ pub struct Video {
     title: String,
     published_at: String,
     url: String,
-    fetched: bool,
+    /// Replaces `fetched` of files written by older versions, see `schema::migrate`
+    #[serde(default)]
+    availability: Availability,
+    /// ISO 8601 duration, e.g. "PT3M33S"
+    #[serde(default)]
+    duration: String,
+    #[serde(default)]
+    view_count: Option<u64>,
+    #[serde(default)]
+    like_count: Option<u64>,
+    #[serde(default)]
+    channel_title: String,
+    #[serde(default)]
+    channel_id: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Thumbnail URLs by quality, e.g. "default", "medium" or "high"
+    #[serde(default)]
+    thumbnails: BTreeMap<String, String>,
 }
 
 impl Default for Video {
@@ -26,7 +63,15 @@ impl Default for Video {
             title: "".into(),
             published_at: "".into(),
             url: "https://www.youtube.com/watch?v=".into(),
-            fetched: false,
+            availability: Availability::Unfetched,
+            duration: "".into(),
+            view_count: None,
+            like_count: None,
+            channel_title: "".into(),
+            channel_id: "".into(),
+            description: "".into(),
+            tags: Vec::new(),
+            thumbnails: BTreeMap::new(),
         }
     }
 }
@@ -44,11 +89,30 @@ impl From<String> for Video {
 
 impl From<ResponseItem> for Video {
     fn from(value: ResponseItem) -> Self {
+        let statistics = value.statistics.unwrap_or_default();
+
         let mut video = Self {
             id: value.id,
             title: value.snippet.title.unwrap_or_default(),
             published_at: value.snippet.published_at.unwrap_or_default(),
-            fetched: true,
+            availability: Availability::Available,
+            duration: value
+                .content_details
+                .and_then(|details| details.duration)
+                .unwrap_or_default(),
+            view_count: statistics.view_count.and_then(|count| count.parse().ok()),
+            like_count: statistics.like_count.and_then(|count| count.parse().ok()),
+            channel_title: value.snippet.channel_title.unwrap_or_default(),
+            channel_id: value.snippet.channel_id.unwrap_or_default(),
+            description: value.snippet.description.unwrap_or_default(),
+            tags: value.snippet.tags.unwrap_or_default(),
+            thumbnails: value
+                .snippet
+                .thumbnails
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(quality, thumbnail)| (quality, thumbnail.url))
+                .collect(),
             ..Default::default()
         };
         video.update_fields();
@@ -64,18 +128,117 @@ impl PartialEq for Video {
 
 impl Display for Video {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let date_and_time = self.published_at.split('T').collect::<Vec<&str>>();
-        let date = date_and_time.first().unwrap_or(&"unknown date");
-
-        write!(
-            f,
-            "{}\n\tID: {}\n\tPublished at: {}\n\tURL: {}",
-            self.title, self.id, date, self.url
-        )
+        write!(f, "{}", Template::default_video().render_video(self))
     }
 }
 
 impl Video {
+    /// Create a video from an entry of a playlist file
+    ///
+    /// The video is treated as not fetched yet, so the API's meta data takes precedence later on
+    /// * `id` - video ID
+    /// * `title` - title of the video
+    /// * `duration_seconds` - duration in seconds, if known
+    /// * `channel_title` - name of the channel
+    pub fn from_entry(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        duration_seconds: Option<u64>,
+        channel_title: impl Into<String>,
+    ) -> Self {
+        let mut video = Self {
+            id: id.into(),
+            title: title.into(),
+            duration: duration_seconds
+                .map(utils::format_iso8601_duration)
+                .unwrap_or_default(),
+            channel_title: channel_title.into(),
+            ..Default::default()
+        };
+        video.update_fields();
+        video
+    }
+
+    /// Return `self` with the given publishing date
+    /// * `published_at` - e.g. "2009-10-25T06:57:33Z"
+    pub fn with_published_at(mut self, published_at: impl Into<String>) -> Self {
+        self.published_at = published_at.into();
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn published_at(&self) -> &str {
+        &self.published_at
+    }
+
+    pub fn availability(&self) -> Availability {
+        self.availability
+    }
+
+    pub fn channel_title(&self) -> &str {
+        &self.channel_title
+    }
+
+    pub fn channel_id(&self) -> &str {
+        &self.channel_id
+    }
+
+    pub fn view_count(&self) -> Option<u64> {
+        self.view_count
+    }
+
+    pub fn like_count(&self) -> Option<u64> {
+        self.like_count
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn thumbnails(&self) -> &BTreeMap<String, String> {
+        &self.thumbnails
+    }
+
+    /// Return the video's duration in seconds, if known
+    pub fn duration_seconds(&self) -> Option<u64> {
+        utils::parse_iso8601_duration(&self.duration)
+    }
+
+    /// Return the names of meta data fields that differ between `self` and `other`
+    ///
+    /// Title, availability and statistics are left out, see `VideoChange`
+    /// * `other` - the same video with newer meta data
+    fn changed_metadata_fields(&self, other: &Self) -> Vec<String> {
+        [
+            ("publishedAt", self.published_at != other.published_at),
+            ("duration", self.duration != other.duration),
+            ("channelTitle", self.channel_title != other.channel_title),
+            ("channelId", self.channel_id != other.channel_id),
+            ("description", self.description != other.description),
+            ("tags", self.tags != other.tags),
+            ("thumbnails", self.thumbnails != other.thumbnails),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect()
+    }
+
     /// Update fields that depend on other fields
     /// e.g. `self.url` depends on `self.id`
     fn update_fields(&mut self) {
@@ -84,14 +247,133 @@ impl Video {
     }
 }
 
+/// Summary of a call to `Playlist::fetch_metadata`
+#[derive(Debug, Default, PartialEq)]
+pub struct FetchReport {
+    /// IDs of videos whose meta data was fetched
+    pub available: Vec<String>,
+    /// IDs of videos the API returned no data for
+    pub unavailable: Vec<String>,
+}
+
+impl Display for FetchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let num_requested = self.available.len() + self.unavailable.len();
+        write!(
+            f,
+            "Fetched meta data for {} of {} videos",
+            self.available.len(),
+            num_requested
+        )?;
+        if !self.unavailable.is_empty() {
+            write!(f, "\nUnavailable (deleted, private or mistyped):")?;
+            for id in &self.unavailable {
+                write!(f, "\n\t- {}", id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Change of a single video detected by `Playlist::refresh_metadata`
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum VideoChange {
+    /// The video's title changed
+    TitleChanged {
+        id: String,
+        from: String,
+        to: String,
+    },
+    /// The API no longer returns data for the video
+    NowUnavailable { id: String, title: String },
+    /// The API returns data for a video that was unavailable or not fetched before
+    NowAvailable { id: String, title: String },
+    /// View and/or like count changed
+    StatisticsChanged {
+        id: String,
+        title: String,
+        view_count: (Option<u64>, Option<u64>),
+        like_count: (Option<u64>, Option<u64>),
+    },
+    /// Other meta data changed, e.g. duration, tags or description
+    MetadataChanged {
+        id: String,
+        title: String,
+        /// Names of the changed fields as stored in the playlist file
+        fields: Vec<String>,
+    },
+}
+
+impl Display for VideoChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = |count: &Option<u64>| match count {
+            Some(count) => count.to_string(),
+            None => "unknown".into(),
+        };
+
+        match self {
+            VideoChange::TitleChanged { id, from, to } => {
+                write!(f, "{}: title changed from \"{}\" to \"{}\"", id, from, to)
+            }
+            VideoChange::NowUnavailable { id, title } => {
+                write!(f, "{}: \"{}\" is now unavailable", id, title)
+            }
+            VideoChange::NowAvailable { id, title } => {
+                write!(f, "{}: \"{}\" is now available", id, title)
+            }
+            VideoChange::StatisticsChanged {
+                id,
+                title,
+                view_count,
+                like_count,
+            } => write!(
+                f,
+                "{}: \"{}\" views {} -> {}, likes {} -> {}",
+                id,
+                title,
+                count(&view_count.0),
+                count(&view_count.1),
+                count(&like_count.0),
+                count(&like_count.1)
+            ),
+            VideoChange::MetadataChanged { id, title, fields } => {
+                write!(f, "{}: \"{}\" changed {}", id, title, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// Advisory lock on a playlist, see `Playlist::lock`
+///
+/// Other processes calling `Playlist::lock` for the same playlist wait until this is dropped
+#[derive(Debug)]
+pub struct PlaylistLock {
+    _file: fs::File,
+}
+
+/// Title stored in a playlist file, used to find a playlist's file without parsing all of it
+#[derive(Debug, Deserialize)]
+struct StoredTitle {
+    title: String,
+}
+
+/// Maximum number of videos YouTube accepts in a single `watch_videos` URL
+const MAX_VIDEOS_PER_URL: usize = 50;
+
 /// Data structure for a playlist
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Playlist {
     title: String,
-    num_items: u8,
+    num_items: usize,
     videos: Vec<Video>,
-    url: String,
+    #[serde(default)]
+    urls: Vec<String>,
 }
 
 impl Default for Playlist {
@@ -100,25 +382,14 @@ impl Default for Playlist {
             title: "untitled".into(),
             num_items: 0,
             videos: Vec::new(),
-            url: "http://www.youtube.com/watch_videos?video_ids=".into(),
+            urls: Vec::new(),
         }
     }
 }
 
 impl Display for Playlist {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let videos = self
-            .videos
-            .iter()
-            .map(|video| format!("{}", video).replace('\t', "\t\t"))
-            .map(|video_string| format!("\t{}", video_string))
-            .collect::<Vec<String>>()
-            .join("\n\n");
-        write!(
-            f,
-            "{}\n----------\nlength: {}\nvideos: \n{}\n\nplaylist URL: {}",
-            self.title, self.num_items, videos, self.url
-        )
+        write!(f, "{}", Template::default_playlist().render_playlist(self))
     }
 }
 
@@ -132,11 +403,39 @@ impl Playlist {
         playlist
     }
 
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn num_items(&self) -> usize {
+        self.num_items
+    }
+
+    pub fn videos(&self) -> &[Video] {
+        &self.videos
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
     /// Update fields that depend on other fields
     /// e.g. `self.num_items` depends on `self.videos`
     fn update_fields(&mut self) {
-        self.num_items = self.videos.len() as u8;
-        self.url = self.compose_playlist_url();
+        self.num_items = self.videos.len();
+        self.urls = self.compose_playlist_urls();
+    }
+
+    /// Return the sum of all known video durations in seconds, saturating at `u64::MAX`
+    pub fn total_duration(&self) -> u64 {
+        self.videos
+            .iter()
+            .filter_map(Video::duration_seconds)
+            .fold(0, u64::saturating_add)
     }
 
     /// Add videos to the playlist
@@ -145,14 +444,29 @@ impl Playlist {
     /// Fields are then updated accordingly
     /// * `ids` - list of video IDs
     pub fn add_videos(&mut self, ids: &[String]) {
-        let mut videos = ids
+        let videos = ids
             .iter()
             .map(|id| id.to_string())
             .map(Video::from)
             .collect::<Vec<Video>>();
-        videos.retain(|video| !self.videos.contains(video));
+        self.push_videos(videos);
+    }
 
-        self.videos.append(&mut videos);
+    /// Add videos including their meta data to the playlist
+    ///
+    /// Videos already in the playlist (or repeated in `videos`) are ignored
+    /// Fields are then updated accordingly
+    ///
+    /// Unlike `add_videos`, which only takes IDs, the videos keep their meta data and availability
+    /// as provided (e.g. read from a playlist file). IDs aren't validated, callers have to check them
+    /// with `ids::parse_video_ids` just like the IDs passed to `add_videos`.
+    /// * `videos` - list of videos
+    pub fn push_videos(&mut self, videos: Vec<Video>) {
+        for video in videos {
+            if !self.videos.contains(&video) {
+                self.videos.push(video);
+            }
+        }
         self.update_fields();
     }
 
@@ -165,71 +479,425 @@ impl Playlist {
         self.update_fields();
     }
 
-    /// Return a `String` containing the playlist URL
+    /// Return the 0-based position of a video in the playlist, if it's part of it
+    /// * `id` - video ID
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.videos.iter().position(|video| video.id == id)
+    }
+
+    /// Append all videos of `other` that aren't part of the playlist yet
     ///
-    /// The URL is composed using the base url and a comma separated list of video IDs
-    fn compose_playlist_url(&self) -> String {
+    /// Videos keep their meta data, the order of `self` comes first
+    /// * `other` - playlist to merge
+    pub fn union(&mut self, other: Playlist) {
+        self.push_videos(other.videos);
+    }
+
+    /// Keep only videos that are also part of `other`
+    /// * `other` - playlist to intersect with
+    pub fn intersect(&mut self, other: &Playlist) {
+        self.videos.retain(|video| other.videos.contains(video));
+        self.update_fields();
+    }
+
+    /// Keep only videos that aren't part of `other`
+    /// * `other` - playlist to subtract
+    pub fn difference(&mut self, other: &Playlist) {
+        self.videos.retain(|video| !other.videos.contains(video));
+        self.update_fields();
+    }
+
+    /// Return a list of playlist URLs
+    ///
+    /// YouTube truncates `watch_videos` URLs after `MAX_VIDEOS_PER_URL` IDs,
+    /// so the videos are split into consecutive parts with one URL each.
+    /// Each URL is composed using the base url and a comma separated list of video IDs
+    fn compose_playlist_urls(&self) -> Vec<String> {
         const BASE_URL: &str = "http://www.youtube.com/watch_videos?video_ids=";
 
+        self.videos
+            .chunks(MAX_VIDEOS_PER_URL)
+            .map(|videos| {
+                let ids: Vec<&str> = videos.iter().map(|video| video.id.as_str()).collect();
+                format!("{}{}", &BASE_URL, &ids.join(","))
+            })
+            .collect()
+    }
+
+    /// Use `provider` to accumulate video meta data in `self.videos`
+    /// Only request data for videos, that have no attached meta data yet
+    /// Videos keep their position in the playlist
+    ///
+    /// Videos the API returns no data for are marked as unavailable instead of failing the request.
+    /// Return a summary of which videos were fetched and which are unavailable.
+    /// * `provider` - source of video meta data
+    pub async fn fetch_metadata(
+        &mut self,
+        provider: &impl MetadataProvider,
+    ) -> Result<FetchReport> {
         let ids: Vec<String> = self
             .videos
             .iter()
+            .filter(|video| video.availability == Availability::Unfetched)
             .map(|video| video.id.to_string())
             .collect();
 
-        format!("{}{}", &BASE_URL, &ids.join(","))
+        if ids.is_empty() {
+            return Ok(FetchReport::default());
+        }
+
+        let mut newly_fetched = provider
+            .fetch_videos(&ids)
+            .await?
+            .into_iter()
+            .map(|item| (item.id.to_string(), Video::from(item)))
+            .collect::<HashMap<String, Video>>();
+
+        let mut report = FetchReport::default();
+        for video in self
+            .videos
+            .iter_mut()
+            .filter(|video| video.availability == Availability::Unfetched)
+        {
+            match newly_fetched.remove(&video.id) {
+                Some(fetched_video) => {
+                    *video = fetched_video;
+                    report.available.push(video.id.to_string());
+                }
+                None => {
+                    video.availability = Availability::Unavailable;
+                    report.unavailable.push(video.id.to_string());
+                }
+            }
+        }
+
+        Ok(report)
     }
 
-    /// Use YouTube's API to accumulate video meta data in `self.videos`
-    /// Only request data for videos, that have no attached meta data yet
-    pub async fn fetch_metadata(&mut self) -> Result<()> {
+    /// Add all videos of a YouTube playlist and fetch their meta data
+    ///
+    /// Videos already in the playlist are ignored, see `Playlist::add_videos`
+    /// * `playlist_id` - ID of the YouTube playlist
+    /// * `provider` - source of video meta data
+    pub async fn import_videos(
+        &mut self,
+        playlist_id: &str,
+        provider: &impl MetadataProvider,
+    ) -> Result<FetchReport> {
+        let ids = provider.fetch_playlist_items(playlist_id).await?;
+        self.add_videos(&ids);
+        self.fetch_metadata(provider).await
+    }
+
+    /// Use `provider` to re-fetch meta data of all videos in `self.videos`
+    ///
+    /// Videos keep their position in the playlist.
+    /// Videos the API no longer returns data for are marked as unavailable but keep their last known meta data.
+    /// Return a list of all detected changes, covering every replaced field of videos that were available before.
+    /// * `provider` - source of video meta data
+    pub async fn refresh_metadata(
+        &mut self,
+        provider: &impl MetadataProvider,
+    ) -> Result<Vec<VideoChange>> {
         let ids: Vec<String> = self
             .videos
             .iter()
-            .filter(|video| !video.fetched)
             .map(|video| video.id.to_string())
             .collect();
 
-        let response = youtube_api::make_video_request(&ids).await?;
-        let mut newly_fetched = response
-            .items
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut newly_fetched = provider
+            .fetch_videos(&ids)
+            .await?
             .into_iter()
-            .map(Video::from)
-            .collect::<Vec<Video>>();
+            .map(|item| (item.id.to_string(), Video::from(item)))
+            .collect::<HashMap<String, Video>>();
+
+        let mut changes = Vec::new();
+        for video in self.videos.iter_mut() {
+            let Some(fetched_video) = newly_fetched.remove(&video.id) else {
+                if video.availability != Availability::Unavailable {
+                    video.availability = Availability::Unavailable;
+                    changes.push(VideoChange::NowUnavailable {
+                        id: video.id.to_string(),
+                        title: video.title.to_string(),
+                    });
+                }
+                continue;
+            };
+
+            if video.availability != Availability::Available {
+                changes.push(VideoChange::NowAvailable {
+                    id: fetched_video.id.to_string(),
+                    title: fetched_video.title.to_string(),
+                });
+            } else {
+                if video.title != fetched_video.title {
+                    changes.push(VideoChange::TitleChanged {
+                        id: video.id.to_string(),
+                        from: video.title.to_string(),
+                        to: fetched_video.title.to_string(),
+                    });
+                }
+                if video.view_count != fetched_video.view_count
+                    || video.like_count != fetched_video.like_count
+                {
+                    changes.push(VideoChange::StatisticsChanged {
+                        id: video.id.to_string(),
+                        title: fetched_video.title.to_string(),
+                        view_count: (video.view_count, fetched_video.view_count),
+                        like_count: (video.like_count, fetched_video.like_count),
+                    });
+                }
+                let fields = video.changed_metadata_fields(&fetched_video);
+                if !fields.is_empty() {
+                    changes.push(VideoChange::MetadataChanged {
+                        id: video.id.to_string(),
+                        title: fetched_video.title.to_string(),
+                        fields,
+                    });
+                }
+            }
+
+            *video = fetched_video;
+        }
+
+        Ok(changes)
+    }
+
+    /// Return the path of the JSON file storing the playlist with the given title, if any
+    ///
+    /// Files are named after the slug of the title (see `utils::slugify`), followed by `-2`, `-3`, ...
+    /// if different titles share the same slug. These are checked first, then a file named after
+    /// the plain title, as written by older versions.
+    /// The title stored inside the file has to match `playlist_title` exactly.
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn find_file_path(playlist_title: &str, file_path: &str) -> Result<Option<PathBuf>> {
+        for path in Playlist::candidate_files(playlist_title, file_path)? {
+            if let Ok(Some(stored_title)) = Playlist::stored_title(&path) {
+                if stored_title == playlist_title {
+                    return Ok(Some(path));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Return a description of every file that could store the playlist with the given title but can't be read
+    ///
+    /// These files are skipped by `Playlist::find_file_path`, so saving the playlist creates a new file next to them.
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn invalid_files(playlist_title: &str, file_path: &str) -> Result<Vec<String>> {
+        Ok(Playlist::candidate_files(playlist_title, file_path)?
+            .into_iter()
+            .filter_map(|path| match Playlist::stored_title(&path) {
+                Ok(_) => None,
+                Err(Error::Json(error)) => Some(format!(
+                    "Skipping invalid playlist file {:?}: {}",
+                    path, error
+                )),
+                Err(error) => Some(format!(
+                    "Skipping unreadable playlist file {:?}: {}",
+                    path, error
+                )),
+            })
+            .collect())
+    }
+
+    /// Return the existing files that could store the playlist with the given title, see `Playlist::find_file_path`
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    fn candidate_files(playlist_title: &str, file_path: &str) -> Result<Vec<PathBuf>> {
+        let directory = utils::expand_path_aliases(PathBuf::from(file_path));
+        let mut candidates = Playlist::slug_files(playlist_title, &directory)?;
+
+        let is_plain_file_name = !playlist_title.is_empty()
+            && !playlist_title.contains(['/', '\\'])
+            && playlist_title != "."
+            && playlist_title != "..";
+        if is_plain_file_name {
+            let path = directory.join(format!("{}.json", playlist_title));
+            if !candidates.contains(&path) && path.is_file() {
+                candidates.push(path);
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// Return the title stored in a playlist file without parsing the whole playlist
+    ///
+    /// Empty files, which older versions left behind when looking up a playlist that didn't exist,
+    /// don't store a title. Fails if the file can't be read or parsed.
+    fn stored_title(path: &Path) -> Result<Option<String>> {
+        let playlist_json = fs::read_to_string(path)?;
+        if playlist_json.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            serde_json::from_str::<StoredTitle>(&playlist_json)?.title,
+        ))
+    }
+
+    /// Return the path of a new JSON file for the playlist with the given title
+    ///
+    /// The first of `<slug>.json`, `<slug>-2.json`, ... that doesn't exist yet or is empty.
+    /// Empty files were left behind by older versions when looking up a playlist that didn't exist.
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    fn new_file_path(playlist_title: &str, file_path: &str) -> PathBuf {
+        let directory = utils::expand_path_aliases(PathBuf::from(file_path));
+        Playlist::slug_file_paths(playlist_title, &directory)
+            .find(|path| match fs::metadata(path) {
+                Ok(metadata) => metadata.is_file() && metadata.len() == 0,
+                Err(_) => true,
+            })
+            .expect("Infinite iterator always yields a path")
+    }
+
+    /// Return `<slug>.json`, `<slug>-2.json`, `<slug>-3.json`, ... inside `directory`
+    fn slug_file_paths(playlist_title: &str, directory: &Path) -> impl Iterator<Item = PathBuf> {
+        let slug = utils::slugify(playlist_title);
+        let directory = directory.to_path_buf();
+        (1..).map(move |number| match number {
+            1 => directory.join(format!("{}.json", slug)),
+            number => directory.join(format!("{}-{}.json", slug, number)),
+        })
+    }
+
+    /// Return the existing files out of `slug_file_paths` in the same order
+    ///
+    /// Only file names are compared, so the order doesn't depend on gaps left by deleted playlists.
+    fn slug_files(playlist_title: &str, directory: &Path) -> Result<Vec<PathBuf>> {
+        let slug = utils::slugify(playlist_title);
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut files = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let number = match file_name.strip_suffix(".json")?.strip_prefix(&slug)? {
+                    "" => 1,
+                    suffix => suffix
+                        .strip_prefix('-')?
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|number| *number >= 2)?,
+                };
+                Some((number, entry.path()))
+            })
+            .filter(|(_, path)| path.is_file())
+            .collect::<Vec<(usize, PathBuf)>>();
+        files.sort();
+
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Take an exclusive advisory lock on a playlist, waiting for other processes holding it
+    ///
+    /// The lock is released when the returned guard is dropped.
+    /// It's taken on a separate `.<slug>.lock` file, since saving replaces the playlist file.
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn lock(playlist_title: &str, file_path: &str) -> Result<PlaylistLock> {
+        Playlist::lock_slug(&utils::slugify(playlist_title), file_path)
+    }
+
+    /// Take the locks of several playlists, see `Playlist::lock`
+    ///
+    /// Locks are taken in the order of the titles' slugs, so processes locking the same playlists can't deadlock.
+    /// Titles sharing a slug share a single lock.
+    /// * `playlist_titles` - names of the playlists
+    /// * `file_path` - path to the save directory
+    pub fn lock_all(playlist_titles: &[&str], file_path: &str) -> Result<Vec<PlaylistLock>> {
+        let mut slugs: Vec<String> = playlist_titles
+            .iter()
+            .map(|playlist_title| utils::slugify(playlist_title))
+            .collect();
+        slugs.sort();
+        slugs.dedup();
+
+        slugs
+            .iter()
+            .map(|slug| Playlist::lock_slug(slug, file_path))
+            .collect()
+    }
+
+    /// Take the lock of the playlists named after `slug`, see `Playlist::lock`
+    /// * `slug` - slug of the playlist title
+    /// * `file_path` - path to the save directory
+    fn lock_slug(slug: &str, file_path: &str) -> Result<PlaylistLock> {
+        let lock_file_path =
+            utils::expand_path_aliases(PathBuf::from(file_path)).join(format!(".{}.lock", slug));
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_file_path)?;
+        FileExt::lock_exclusive(&file)?;
+
+        Ok(PlaylistLock { _file: file })
+    }
 
-        let num_requested = ids.len();
-        let num_fetched = newly_fetched.len();
-
-        if num_fetched == num_requested {
-            self.videos.retain(|video| video.fetched);
-            self.videos.append(&mut newly_fetched);
-            Ok(())
-        } else {
-            Err(Error::NotEnoughResponseItems(
-                num_requested as u8,
-                num_fetched as u8,
-            ))
+    /// Return `true` if a playlist file with the given title exists
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn exists(playlist_title: &str, file_path: &str) -> Result<bool> {
+        Ok(Playlist::find_file_path(playlist_title, file_path)?.is_some())
+    }
+
+    /// Delete the JSON file of a playlist
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn delete_playlist(playlist_title: &str, file_path: &str) -> Result<()> {
+        match Playlist::find_file_path(playlist_title, file_path)? {
+            Some(file_path) => Ok(fs::remove_file(file_path)?),
+            None => Err(Error::PlaylistNotFound(playlist_title.to_string())),
         }
     }
 
-    /// Serialize a `Playlist` instance and write content to a JSON file using the playlist's title as file name
+    /// Serialize a `Playlist` instance and write content to a JSON file
+    ///
+    /// The existing file of the playlist is replaced, new playlists get a file named after the slug of their title
     /// * `file_path` - path to the save directory
     pub fn save_playlist(&self, file_path: impl Into<String>) -> Result<()> {
         let file_path = file_path.into();
-        let mut file_path: PathBuf = [&file_path, &self.title].iter().collect();
-        file_path.set_extension("json");
-
-        file_path = utils::expand_path_aliases(file_path);
+        let file_path = match Playlist::find_file_path(&self.title, &file_path)? {
+            Some(existing_file_path) => existing_file_path,
+            None => Playlist::new_file_path(&self.title, &file_path),
+        };
 
-        let playlist_json: String = serde_json::to_string(self)?;
-        fs::write(file_path, playlist_json)?;
+        let playlist_json: String = serde_json::to_string(&PlaylistFile::from(self))?;
+        utils::write_atomic(&file_path, playlist_json)?;
 
         Ok(())
     }
 
+    /// Parse a playlist from the content of a playlist file
+    ///
+    /// Files written by older versions are migrated to the current schema first, see `schema::migrate`
+    /// * `playlist_json` - content of the file
+    pub fn from_json(playlist_json: &str) -> Result<Playlist> {
+        let mut playlist: Playlist = serde_json::from_value(schema::migrate(playlist_json)?)?;
+        playlist.update_fields();
+        Ok(playlist)
+    }
+
     /// Return a `Playlist` instance
     ///
-    /// Try to load content from a JSON file and deserialize into `Playlist` instance
+    /// Try to load content from a JSON file and deserialize into `Playlist` instance.
+    /// Returns `None` if the playlist doesn't exist, the disk is never modified.
     /// * `playlist_title` - name of the playlist
     /// * `file_path` - path to the save directory
     pub fn load_playlist(
@@ -237,31 +905,35 @@ impl Playlist {
         file_path: impl Into<String>,
     ) -> Result<Option<Playlist>> {
         let playlist_title = playlist_title.into();
-        let file_path = file_path.into();
-        let mut file_path: PathBuf = [&file_path, &playlist_title].iter().collect();
-        file_path.set_extension("json");
-
-        file_path = utils::expand_path_aliases(file_path);
-
-        match utils::load_or_create_file(file_path)? {
-            None => {
-                println!(
-                    "Playlist {0} does not exist, creating {0} instead",
-                    &playlist_title
-                );
-                Ok(None)
-            }
-            Some(playlist_json) => {
-                let playlist = serde_json::from_str(&playlist_json)?;
-                Ok(playlist)
+
+        match Playlist::find_file_path(&playlist_title, &file_path.into())? {
+            None => Ok(None),
+            Some(file_path) => {
+                let playlist_json = fs::read_to_string(file_path)?;
+                Ok(Some(Playlist::from_json(&playlist_json)?))
             }
         }
     }
+
+    /// Return an existing `Playlist` instance
+    ///
+    /// Fails with `Error::PlaylistNotFound` if the playlist doesn't exist
+    /// * `playlist_title` - name of the playlist
+    /// * `file_path` - path to the save directory
+    pub fn load_existing(
+        playlist_title: impl Into<String>,
+        file_path: impl Into<String>,
+    ) -> Result<Playlist> {
+        let playlist_title = playlist_title.into();
+        Playlist::load_playlist(&playlist_title, file_path)?
+            .ok_or(Error::PlaylistNotFound(playlist_title))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::InMemoryProvider;
 
     #[test]
     fn test_video() {
@@ -316,7 +988,7 @@ mod tests {
                 title: "test".into(),
                 videos: vec!["id_1".to_string().into(), "id_2".to_string().into()],
                 num_items: 2,
-                url: "http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()
+                urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()]
             },
             playlist_2
         );
@@ -346,7 +1018,7 @@ mod tests {
                         ..Default::default()
                     }
                 ],
-                url: "http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()
+                urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()]
             }
         );
 
@@ -371,7 +1043,7 @@ mod tests {
                         ..Default::default()
                     }
                 ],
-                url: "http://www.youtube.com/watch_videos?video_ids=id_1,id_2,id_3".into()
+                urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2,id_3".into()]
             }
         );
     }
@@ -386,7 +1058,7 @@ mod tests {
                 "id_2".to_string().into(),
                 "id_3".to_string().into(),
             ],
-            url: "http://www.youtube.com/watch_videos?video_ids=id_1,id_2,id_3".into(),
+            urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2,id_3".into()],
         };
 
         playlist.remove_videos(&["id_1".into(), "id_2".into()]);
@@ -397,7 +1069,7 @@ mod tests {
                 title: "test".into(),
                 num_items: 1,
                 videos: vec!["id_3".to_string().into()],
-                url: "http://www.youtube.com/watch_videos?video_ids=id_3".into()
+                urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_3".into()]
             }
         );
 
@@ -409,11 +1081,54 @@ mod tests {
                 title: "test".into(),
                 num_items: 0,
                 videos: vec![],
-                url: "http://www.youtube.com/watch_videos?video_ids=".into()
+                urls: vec![]
             }
         );
     }
 
+    #[test]
+    fn test_position() {
+        let mut playlist = Playlist::new("test");
+        playlist.add_videos(&["id_1".into(), "id_2".into()]);
+
+        assert_eq!(playlist.position("id_1"), Some(0));
+        assert_eq!(playlist.position("id_2"), Some(1));
+        assert_eq!(playlist.position("id_3"), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let playlist = |ids: &[&str]| {
+            let mut playlist = Playlist::new("test");
+            playlist.add_videos(&ids.iter().map(|id| id.to_string()).collect::<Vec<String>>());
+            playlist
+        };
+        let ids = |playlist: &Playlist| {
+            playlist
+                .videos()
+                .iter()
+                .map(|video| video.id().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let mut union = playlist(&["id_3", "id_1"]);
+        union.union(playlist(&["id_2", "id_1", "id_4"]));
+        assert_eq!(ids(&union), vec!["id_3", "id_1", "id_2", "id_4"]);
+        assert_eq!(union.num_items(), 4);
+
+        let mut intersection = playlist(&["id_3", "id_1", "id_2"]);
+        intersection.intersect(&playlist(&["id_2", "id_3"]));
+        assert_eq!(ids(&intersection), vec!["id_3", "id_2"]);
+        assert_eq!(
+            intersection.urls(),
+            ["http://www.youtube.com/watch_videos?video_ids=id_3,id_2".to_string()]
+        );
+
+        let mut difference = playlist(&["id_3", "id_1", "id_2"]);
+        difference.difference(&playlist(&["id_1", "id_4"]));
+        assert_eq!(ids(&difference), vec!["id_3", "id_2"]);
+    }
+
     #[test]
     fn test_compose_url() {
         let playlist = Playlist {
@@ -423,8 +1138,31 @@ mod tests {
         };
 
         assert_eq!(
-            playlist.compose_playlist_url(),
-            "http://www.youtube.com/watch_videos?video_ids=id_1,id_2".to_string()
+            playlist.compose_playlist_urls(),
+            vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2".to_string()]
+        );
+
+        let ids: Vec<String> = (0..120).map(|i| format!("id_{}", i)).collect();
+        let mut playlist = Playlist::new("test");
+        playlist.add_videos(&ids);
+
+        assert_eq!(playlist.num_items, 120);
+        assert_eq!(
+            playlist.urls,
+            vec![
+                format!(
+                    "http://www.youtube.com/watch_videos?video_ids={}",
+                    ids[0..50].join(",")
+                ),
+                format!(
+                    "http://www.youtube.com/watch_videos?video_ids={}",
+                    ids[50..100].join(",")
+                ),
+                format!(
+                    "http://www.youtube.com/watch_videos?video_ids={}",
+                    ids[100..120].join(",")
+                ),
+            ]
         );
     }
 
@@ -439,8 +1177,8 @@ mod tests {
 
         assert_eq!(playlist.num_items, 2);
         assert_eq!(
-            playlist.url,
-            "http://www.youtube.com/watch_videos?video_ids=id_1,id_2".to_string()
+            playlist.urls,
+            vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2".to_string()]
         );
         assert_eq!(
             playlist,
@@ -457,26 +1195,73 @@ mod tests {
                         ..Default::default()
                     },
                 ],
-                url: "http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()
+                urls: vec!["http://www.youtube.com/watch_videos?video_ids=id_1,id_2".into()]
             }
         );
     }
+    const VIDEOS_FIXTURE: &str = r#"{
+        "kind": "youtube#videoListResponse",
+        "items": [
+            {
+                "kind": "youtube#video",
+                "id": "dQw4w9WgXcQ",
+                "snippet": {
+                    "publishedAt": "2009-10-25T06:57:33Z",
+                    "channelId": "UCuAXFkgsw1L7xaCfnd5JJOw",
+                    "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)",
+                    "channelTitle": "Rick Astley",
+                    "description": "The official video for “Never Gonna Give You Up” by Rick Astley",
+                    "tags": ["rick astley", "never gonna give you up"],
+                    "thumbnails": {
+                        "default": {
+                            "url": "https://i.ytimg.com/vi/dQw4w9WgXcQ/default.jpg",
+                            "width": 120,
+                            "height": 90
+                        }
+                    }
+                },
+                "contentDetails": {
+                    "duration": "PT3M33S"
+                },
+                "statistics": {
+                    "viewCount": "1500000000",
+                    "likeCount": "17000000"
+                }
+            },
+            {
+                "kind": "youtube#video",
+                "id": "y6120QOlsfU",
+                "snippet": {
+                    "publishedAt": "2009-10-02T22:45:58Z",
+                    "title": "Darude - Sandstorm"
+                },
+                "contentDetails": {
+                    "duration": "PT7M24S"
+                }
+            }
+        ]
+    }"#;
+
+    fn fixture_provider() -> InMemoryProvider {
+        InMemoryProvider::from_fixture(VIDEOS_FIXTURE).expect("Fixture should be valid JSON")
+    }
+
     #[tokio::test]
-    #[ignore = "test requires API key"]
+    #[allow(clippy::iter_next_slice)]
     async fn test_fetch_metadata() -> Result<()> {
         let mut playlist = Playlist {
             title: "test".into(),
             videos: vec!["dQw4w9WgXcQ".to_string().into()],
             num_items: 1,
-            url: "http://www.youtube.com/watch_videos?video_ids=dQw4w9WgXcQ".into(),
+            urls: vec!["http://www.youtube.com/watch_videos?video_ids=dQw4w9WgXcQ".into()],
         };
-        playlist.fetch_metadata().await?;
+        playlist.fetch_metadata(&fixture_provider()).await?;
 
         assert_eq!(
             playlist
                 .videos
                 .iter()
-                .filter(|video| !video.fetched)
+                .filter(|video| video.availability != Availability::Available)
                 .count(),
             0
         );
@@ -508,6 +1293,294 @@ mod tests {
             "2009-10-25T06:57:33Z"
         );
 
+        let video = playlist
+            .videos
+            .first()
+            .expect("Test playlist should have one video");
+        assert_eq!(video.duration_seconds(), Some(213));
+        assert_eq!(video.view_count, Some(1_500_000_000));
+        assert_eq!(video.like_count, Some(17_000_000));
+        assert_eq!(video.channel_title, "Rick Astley");
+        assert_eq!(video.channel_id, "UCuAXFkgsw1L7xaCfnd5JJOw");
+        assert!(video.description.starts_with("The official video"));
+        assert_eq!(video.tags.len(), 2);
+        assert_eq!(
+            video.thumbnails.get("default").map(String::as_str),
+            Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/default.jpg")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_metadata_unavailable() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.add_videos(&[
+            "aaaaaaaaaaa".into(),
+            "y6120QOlsfU".into(),
+            "dQw4w9WgXcQ".into(),
+        ]);
+
+        let report = playlist.fetch_metadata(&fixture_provider()).await?;
+
+        assert_eq!(
+            report,
+            FetchReport {
+                available: vec!["y6120QOlsfU".into(), "dQw4w9WgXcQ".into()],
+                unavailable: vec!["aaaaaaaaaaa".into()],
+            }
+        );
+        assert_eq!(
+            playlist
+                .videos
+                .iter()
+                .map(|video| (video.id.as_str(), video.availability))
+                .collect::<Vec<_>>(),
+            vec![
+                ("aaaaaaaaaaa", Availability::Unavailable),
+                ("y6120QOlsfU", Availability::Available),
+                ("dQw4w9WgXcQ", Availability::Available),
+            ]
+        );
+
+        assert_eq!(playlist.total_duration(), 213 + 444);
+
+        // Nothing left to fetch
+        let report = playlist.fetch_metadata(&fixture_provider()).await?;
+        assert_eq!(report, FetchReport::default());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_metadata() -> Result<()> {
+        let mut playlist = Playlist::new("test");
+        playlist.add_videos(&["dQw4w9WgXcQ".into(), "y6120QOlsfU".into()]);
+        playlist.fetch_metadata(&fixture_provider()).await?;
+
+        // Unchanged meta data
+        let changes = playlist.refresh_metadata(&fixture_provider()).await?;
+        assert!(changes.is_empty());
+
+        // Renamed, new statistics and removed
+        let mut item = fixture_provider()
+            .fetch_videos(&["dQw4w9WgXcQ".into()])
+            .await?
+            .remove(0);
+        item.snippet.title = Some("Never Gonna Give You Up".into());
+        item.statistics = Some(crate::youtube_api::ResponseStatistics {
+            view_count: Some("1500000001".into()),
+            like_count: Some("17000000".into()),
+            comment_count: None,
+        });
+        item.snippet.tags = Some(vec!["rick astley".into()]);
+        item.content_details = Some(crate::youtube_api::ResponseContentDetails {
+            duration: Some("PT3M34S".into()),
+        });
+        let changes = playlist
+            .refresh_metadata(&InMemoryProvider::new([item]))
+            .await?;
+
+        assert_eq!(
+            changes,
+            vec![
+                VideoChange::TitleChanged {
+                    id: "dQw4w9WgXcQ".into(),
+                    from: "Rick Astley - Never Gonna Give You Up (Official Music Video)".into(),
+                    to: "Never Gonna Give You Up".into(),
+                },
+                VideoChange::StatisticsChanged {
+                    id: "dQw4w9WgXcQ".into(),
+                    title: "Never Gonna Give You Up".into(),
+                    view_count: (Some(1_500_000_000), Some(1_500_000_001)),
+                    like_count: (Some(17_000_000), Some(17_000_000)),
+                },
+                VideoChange::MetadataChanged {
+                    id: "dQw4w9WgXcQ".into(),
+                    title: "Never Gonna Give You Up".into(),
+                    fields: vec!["duration".into(), "tags".into()],
+                },
+                VideoChange::NowUnavailable {
+                    id: "y6120QOlsfU".into(),
+                    title: "Darude - Sandstorm".into(),
+                },
+            ]
+        );
+        assert_eq!(playlist.videos[0].title, "Never Gonna Give You Up");
+        assert_eq!(playlist.videos[0].duration, "PT3M34S");
+        assert_eq!(playlist.videos[1].title, "Darude - Sandstorm");
+        assert_eq!(playlist.videos[1].availability, Availability::Unavailable);
+
+        // Available again
+        let changes = playlist.refresh_metadata(&fixture_provider()).await?;
+        assert_eq!(changes.len(), 4);
+        assert!(changes.contains(&VideoChange::NowAvailable {
+            id: "y6120QOlsfU".into(),
+            title: "Darude - Sandstorm".into(),
+        }));
+        assert!(changes.contains(&VideoChange::MetadataChanged {
+            id: "dQw4w9WgXcQ".into(),
+            title: "Rick Astley - Never Gonna Give You Up (Official Music Video)".into(),
+            fields: vec!["duration".into(), "tags".into()],
+        }));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_videos() -> Result<()> {
+        const LIST_ID: &str = "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG";
+        let provider = fixture_provider().with_playlist(LIST_ID, &["y6120QOlsfU", "dQw4w9WgXcQ"]);
+
+        let mut playlist = Playlist::new("test");
+        playlist.add_videos(&["dQw4w9WgXcQ".into()]);
+        let report = playlist.import_videos(LIST_ID, &provider).await?;
+
+        assert_eq!(report.available.len(), 2);
+        assert_eq!(
+            playlist
+                .videos
+                .iter()
+                .map(|video| video.id.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["dQw4w9WgXcQ", "y6120QOlsfU"]
+        );
+        assert_eq!(playlist.num_items, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_playlist_files() -> Result<()> {
+        let directory = std::env::temp_dir().join(format!("oxysound-test-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let directory = directory.to_str().expect("Temp dir is valid Unicode");
+
+        let mut playlist = Playlist::new("music");
+        playlist.add_videos(&["dQw4w9WgXcQ".into()]);
+        playlist.save_playlist(directory)?;
+        assert!(Playlist::exists("music", directory)?);
+
+        playlist.set_title("renamed");
+        playlist.save_playlist(directory)?;
+        Playlist::delete_playlist("music", directory)?;
+        assert!(!Playlist::exists("music", directory)?);
+        assert!(matches!(
+            Playlist::delete_playlist("music", directory),
+            Err(Error::PlaylistNotFound(_))
+        ));
+
+        let loaded = Playlist::load_playlist("renamed", directory)?.expect("Playlist was saved");
+        assert_eq!(loaded.title(), "renamed");
+        assert_eq!(loaded.videos(), playlist.videos());
+
+        assert!(Playlist::load_playlist("missing", directory)?.is_none());
+        assert!(matches!(
+            Playlist::load_existing("missing", directory),
+            Err(Error::PlaylistNotFound(_))
+        ));
+        assert!(!Playlist::exists("missing", directory)?);
+        assert_eq!(fs::read_dir(directory)?.count(), 1);
+
+        fs::remove_dir_all(directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_playlist_lock() -> Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("oxysound-test-lock-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let directory = directory.to_str().expect("Temp dir is valid Unicode");
+
+        let lock = Playlist::lock("My Music", directory)?;
+        let lock_file = fs::File::open(format!("{}/.my-music.lock", directory))?;
+        assert!(!FileExt::try_lock_exclusive(&lock_file)?);
+
+        // Other playlists aren't affected
+        drop(Playlist::lock("other", directory)?);
+
+        drop(lock);
+        assert!(FileExt::try_lock_exclusive(&lock_file)?);
+        FileExt::unlock(&lock_file)?;
+
+        // Titles sharing a slug are locked only once
+        let locks = Playlist::lock_all(&["other", "my music", "My Music"], directory)?;
+        assert_eq!(locks.len(), 2);
+        assert!(!FileExt::try_lock_exclusive(&lock_file)?);
+        drop(locks);
+        assert!(FileExt::try_lock_exclusive(&lock_file)?);
+
+        fs::remove_dir_all(directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_playlist_file_names() -> Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("oxysound-test-names-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let directory_path = directory.clone();
+        let directory = directory.to_str().expect("Temp dir is valid Unicode");
+
+        for title in ["Music", "music", "../escape", "a/b: c", ".hidden"] {
+            Playlist::new(title).save_playlist(directory)?;
+        }
+        let mut file_names = fs::read_dir(&directory_path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+            .collect::<Result<Vec<String>>>()?;
+        file_names.sort();
+        assert_eq!(
+            file_names,
+            [
+                "a-b-c.json",
+                "escape.json",
+                "hidden.json",
+                "music-2.json",
+                "music.json"
+            ]
+        );
+
+        for title in ["Music", "music", "../escape", "a/b: c", ".hidden"] {
+            let loaded = Playlist::load_playlist(title, directory)?.expect("Playlist was saved");
+            assert_eq!(loaded.title(), title);
+        }
+        assert!(!Playlist::exists("MUSIC", directory)?);
+
+        fs::write(
+            directory_path.join("My Music.json"),
+            r#"{ "title": "My Music" }"#,
+        )?;
+        assert!(Playlist::exists("My Music", directory)?);
+        Playlist::new("My Music").save_playlist(directory)?;
+        assert!(directory_path.join("My Music.json").is_file());
+        assert!(!directory_path.join("my-music.json").exists());
+
+        Playlist::delete_playlist("Music", directory)?;
+        assert!(Playlist::exists("music", directory)?);
+        assert!(!directory_path.join("music.json").exists());
+
+        // Empty files left behind by older versions are reused, invalid files are kept
+        fs::write(directory_path.join("typo.json"), "")?;
+        fs::write(directory_path.join("broken.json"), "{")?;
+        assert!(!Playlist::exists("typo", directory)?);
+        Playlist::new("typo").save_playlist(directory)?;
+        Playlist::new("broken").save_playlist(directory)?;
+        assert_eq!(
+            Playlist::find_file_path("typo", directory)?,
+            Some(directory_path.join("typo.json"))
+        );
+        assert_eq!(
+            Playlist::find_file_path("broken", directory)?,
+            Some(directory_path.join("broken-2.json"))
+        );
+        assert_eq!(fs::read_to_string(directory_path.join("broken.json"))?, "{");
+        assert!(Playlist::invalid_files("typo", directory)?.is_empty());
+        let invalid_files = Playlist::invalid_files("broken", directory)?;
+        assert_eq!(invalid_files.len(), 1);
+        assert!(invalid_files[0].contains("broken.json"));
+
+        fs::remove_dir_all(directory)?;
         Ok(())
     }
 }
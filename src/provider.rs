@@ -0,0 +1,95 @@
+//! Abstraction over sources of video meta data
+
+use crate::prelude::*;
+use crate::youtube_api::ResponseItem;
+#[cfg(test)]
+use std::collections::HashMap;
+
+/// Source of video meta data
+pub trait MetadataProvider {
+    /// Return response items for the requested videos
+    ///
+    /// Videos the source has no data for are simply missing from the result
+    /// * `video_ids` - list of video IDs
+    async fn fetch_videos(&self, video_ids: &[String]) -> Result<Vec<ResponseItem>>;
+
+    /// Return the IDs of all videos in a YouTube playlist, keeping their order
+    /// * `playlist_id` - ID of the YouTube playlist
+    async fn fetch_playlist_items(&self, playlist_id: &str) -> Result<Vec<String>>;
+
+    /// Return the IDs of videos matching `query`, ranked by relevance
+    /// * `query` - search terms
+    /// * `limit` - maximum number of results
+    async fn search_videos(&self, query: &str, limit: usize) -> Result<Vec<String>>;
+}
+
+/// Provider serving a fixed set of response items from memory
+///
+/// Allows testing everything that depends on meta data without network access
+#[cfg(test)]
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryProvider {
+    items: Vec<ResponseItem>,
+    playlists: HashMap<String, Vec<String>>,
+}
+
+#[cfg(test)]
+impl InMemoryProvider {
+    pub fn new(items: impl IntoIterator<Item = ResponseItem>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Return `self` serving a YouTube playlist with the given video IDs
+    /// * `playlist_id` - ID of the YouTube playlist
+    /// * `video_ids` - IDs of the videos in the playlist
+    pub fn with_playlist(mut self, playlist_id: &str, video_ids: &[&str]) -> Self {
+        self.playlists.insert(
+            playlist_id.to_string(),
+            video_ids.iter().map(|id| id.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Create a provider from a JSON fixture shaped like a `videos` API response
+    /// * `json` - content of the fixture
+    pub fn from_fixture(json: &str) -> Result<Self> {
+        let response: crate::youtube_api::Response = serde_json::from_str(json)?;
+        Ok(Self::new(response.items))
+    }
+}
+
+#[cfg(test)]
+impl MetadataProvider for InMemoryProvider {
+    async fn fetch_videos(&self, video_ids: &[String]) -> Result<Vec<ResponseItem>> {
+        Ok(self
+            .items
+            .iter()
+            .filter(|item| video_ids.contains(&item.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_playlist_items(&self, playlist_id: &str) -> Result<Vec<String>> {
+        Ok(self.playlists.get(playlist_id).cloned().unwrap_or_default())
+    }
+
+    /// Return videos whose title contains `query`, ignoring case
+    async fn search_videos(&self, query: &str, limit: usize) -> Result<Vec<String>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .items
+            .iter()
+            .filter(|item| {
+                item.snippet
+                    .title
+                    .as_ref()
+                    .is_some_and(|title| title.to_lowercase().contains(&query))
+            })
+            .map(|item| item.id.to_string())
+            .take(limit)
+            .collect())
+    }
+}
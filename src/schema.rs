@@ -0,0 +1,200 @@
+//! Versioning of the playlist file format
+//!
+//! Every playlist file stores the version of its format as `schemaVersion`.
+//! Files written by older versions are upgraded step by step when loading.
+//! Additional fields with a sensible default (`#[serde(default)]`) don't need a new version,
+//! renamed fields or fields changing their meaning do.
+
+use crate::playlist::Playlist;
+use crate::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Version of the playlist file format written by this binary
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Upgrade of a playlist file from one version to the next
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades a file of version `n` to version `n + 1`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Playlist as written to a playlist file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistFile<'a> {
+    schema_version: u64,
+    #[serde(flatten)]
+    playlist: &'a Playlist,
+}
+
+impl<'a> From<&'a Playlist> for PlaylistFile<'a> {
+    fn from(value: &'a Playlist) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            playlist: value,
+        }
+    }
+}
+
+/// Upgrade the content of a playlist file to the current `SCHEMA_VERSION`
+///
+/// Files without `schemaVersion` were written before versioning was introduced and are treated as version 0.
+/// Fails with `Error::UnsupportedSchemaVersion` if the file was written by a newer version of the binary.
+/// * `playlist_json` - content of the playlist file
+pub fn migrate(playlist_json: &str) -> Result<Value> {
+    let mut value: Value = serde_json::from_str(playlist_json)?;
+    let Some(playlist) = value.as_object_mut() else {
+        return Ok(value);
+    };
+
+    let version = playlist
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version > SCHEMA_VERSION {
+        return Err(Error::UnsupportedSchemaVersion(version, SCHEMA_VERSION));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(playlist);
+    }
+    playlist.insert("schemaVersion".into(), SCHEMA_VERSION.into());
+
+    Ok(value)
+}
+
+/// Version 0 to 1
+///
+/// - `Video.fetched: bool` was replaced by `Video.availability`
+/// - `Playlist.url: String` was replaced by `Playlist.urls`, one URL per 50 videos
+fn migrate_v0_to_v1(playlist: &mut Map<String, Value>) {
+    if let Some(url) = playlist.remove("url") {
+        playlist
+            .entry("urls")
+            .or_insert_with(|| Value::Array(url.as_str().map(Value::from).into_iter().collect()));
+    }
+
+    let videos = playlist
+        .get_mut("videos")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for video in videos {
+        if let Some(fetched) = video.remove("fetched") {
+            let availability = match fetched.as_bool() {
+                Some(true) => "available",
+                _ => "unfetched",
+            };
+            video
+                .entry("availability")
+                .or_insert_with(|| availability.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playlist::Availability;
+
+    /// Playlist file written before versioning was introduced
+    const V0_PLAYLIST: &str = r#"{
+        "title": "music",
+        "numItems": 2,
+        "videos": [
+            {
+                "id": "dQw4w9WgXcQ",
+                "title": "Rick Astley - Never Gonna Give You Up (Official Music Video)",
+                "publishedAt": "2009-10-25T06:57:33Z",
+                "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "fetched": true
+            },
+            {
+                "id": "y6120QOlsfU",
+                "title": "",
+                "publishedAt": "",
+                "url": "https://www.youtube.com/watch?v=y6120QOlsfU",
+                "fetched": false
+            }
+        ],
+        "url": "http://www.youtube.com/watch_videos?video_ids=dQw4w9WgXcQ,y6120QOlsfU"
+    }"#;
+
+    #[test]
+    fn test_migrate_v0() -> Result<()> {
+        let value = migrate(V0_PLAYLIST)?;
+        assert_eq!(value["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(value.get("url"), None);
+        assert_eq!(value["videos"][0].get("fetched"), None);
+
+        let playlist = Playlist::from_json(V0_PLAYLIST)?;
+        assert_eq!(playlist.title(), "music");
+        assert_eq!(playlist.num_items(), 2);
+        assert_eq!(playlist.videos()[0].availability(), Availability::Available);
+        assert_eq!(playlist.videos()[1].availability(), Availability::Unfetched);
+        assert_eq!(
+            playlist.urls(),
+            ["http://www.youtube.com/watch_videos?video_ids=dQw4w9WgXcQ,y6120QOlsfU".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_unversioned_availability() -> Result<()> {
+        // Written after `fetched` was replaced by `availability` but before versioning was introduced
+        let playlist = Playlist::from_json(
+            r#"{
+                "title": "music",
+                "numItems": 3,
+                "videos": [
+                    { "id": "aaaaaaaaaaa", "title": "", "publishedAt": "", "url": "", "availability": "unavailable" },
+                    { "id": "dQw4w9WgXcQ", "title": "", "publishedAt": "", "url": "", "fetched": true, "availability": "unfetched" },
+                    { "id": "y6120QOlsfU", "title": "", "publishedAt": "", "url": "" }
+                ],
+                "urls": []
+            }"#,
+        )?;
+
+        assert_eq!(
+            playlist
+                .videos()
+                .iter()
+                .map(|video| video.availability())
+                .collect::<Vec<_>>(),
+            [
+                Availability::Unavailable,
+                Availability::Unfetched,
+                Availability::Unfetched,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let playlist = Playlist::from_json(V0_PLAYLIST)?;
+        let playlist_json = serde_json::to_string(&PlaylistFile::from(&playlist))?;
+
+        assert!(playlist_json.starts_with(&format!("{{\"schemaVersion\":{}", SCHEMA_VERSION)));
+        assert_eq!(Playlist::from_json(&playlist_json)?, playlist);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let playlist_json = format!(
+            r#"{{ "schemaVersion": {}, "title": "music" }}"#,
+            SCHEMA_VERSION + 1
+        );
+
+        assert!(matches!(
+            Playlist::from_json(&playlist_json),
+            Err(Error::UnsupportedSchemaVersion(version, SCHEMA_VERSION)) if version == SCHEMA_VERSION + 1
+        ));
+    }
+}
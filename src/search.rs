@@ -0,0 +1,213 @@
+//! Search for videos, either offline across the meta data of saved videos or via a `MetadataProvider`
+
+use crate::playlist::Video;
+use crate::prelude::*;
+use crate::provider::MetadataProvider;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// How a query is compared to the searched text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The text contains the query, ignoring case
+    Substring,
+    /// Every word of the query is contained in or similar to a word of the text, ignoring case
+    Fuzzy,
+}
+
+/// Part of a video's meta data that matched a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchedField {
+    Title,
+    ChannelTitle,
+    Description,
+    Tags,
+}
+
+impl Display for MatchedField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchedField::Title => write!(f, "title"),
+            MatchedField::ChannelTitle => write!(f, "channel"),
+            MatchedField::Description => write!(f, "description"),
+            MatchedField::Tags => write!(f, "tags"),
+        }
+    }
+}
+
+/// Return the first field of `video` matching `query`, if any
+///
+/// Fields are checked in the order title, channel name, description and tags
+/// * `video` - video to search
+/// * `query` - search query
+/// * `mode` - how to compare the query to the fields
+pub fn match_video(video: &Video, query: &str, mode: MatchMode) -> Option<MatchedField> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    let tags = video.tags().join(" ");
+    let fields = [
+        (MatchedField::Title, video.title()),
+        (MatchedField::ChannelTitle, video.channel_title()),
+        (MatchedField::Description, video.description()),
+        (MatchedField::Tags, tags.as_str()),
+    ];
+
+    fields
+        .iter()
+        .find(|(_, text)| matches(&text.to_lowercase(), &query, mode))
+        .map(|(field, _)| *field)
+}
+
+/// Search videos matching `query` and return them including their meta data, ranked by relevance
+/// * `query` - search terms
+/// * `limit` - maximum number of results
+/// * `provider` - source of search results and video meta data
+pub async fn search_videos(
+    query: &str,
+    limit: usize,
+    provider: &impl MetadataProvider,
+) -> Result<Vec<Video>> {
+    let ids = provider.search_videos(query, limit).await?;
+
+    let mut items = provider
+        .fetch_videos(&ids)
+        .await?
+        .into_iter()
+        .map(|item| (item.id.to_string(), item))
+        .collect::<HashMap<_, _>>();
+
+    Ok(ids
+        .iter()
+        .filter_map(|id| items.remove(id))
+        .map(Video::from)
+        .collect())
+}
+
+/// Return `true` if `text` matches `query`, both are expected to be lowercase
+fn matches(text: &str, query: &str, mode: MatchMode) -> bool {
+    match mode {
+        MatchMode::Substring => text.contains(query),
+        MatchMode::Fuzzy => {
+            let words = text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<&str>>();
+            query.split_whitespace().all(|query_word| {
+                text.contains(query_word) || words.iter().any(|word| is_similar(word, query_word))
+            })
+        }
+    }
+}
+
+/// Return `true` if `word` is at most a few typos away from `query_word`
+///
+/// One edit is allowed per four characters of `query_word`, at least one
+fn is_similar(word: &str, query_word: &str) -> bool {
+    let max_distance = (query_word.chars().count() / 4).max(1);
+    word.chars().count().abs_diff(query_word.chars().count()) <= max_distance
+        && edit_distance(word, query_word) <= max_distance
+}
+
+/// Return the edit distance between `a` and `b`
+///
+/// Counts insertions, deletions, substitutions and transpositions of adjacent characters
+/// (optimal string alignment distance)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+
+    for i in 0..a.len() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                current[j + 1] = current[j + 1].min(before_previous[j - 1] + 1);
+            }
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rick_astley() -> Video {
+        Video::from_entry(
+            "dQw4w9WgXcQ",
+            "Rick Astley - Never Gonna Give You Up",
+            Some(213),
+            "RickAstleyVEVO",
+        )
+    }
+
+    #[test]
+    fn test_match_video() {
+        let video = rick_astley();
+
+        assert_eq!(
+            match_video(&video, "never gonna", MatchMode::Substring),
+            Some(MatchedField::Title)
+        );
+        assert_eq!(
+            match_video(&video, "VEVO", MatchMode::Substring),
+            Some(MatchedField::ChannelTitle)
+        );
+        assert_eq!(match_video(&video, "nevr gona", MatchMode::Substring), None);
+        assert_eq!(match_video(&video, "", MatchMode::Substring), None);
+
+        assert_eq!(
+            match_video(&video, "nevr gona", MatchMode::Fuzzy),
+            Some(MatchedField::Title)
+        );
+        assert_eq!(
+            match_video(&video, "gonna astley", MatchMode::Fuzzy),
+            Some(MatchedField::Title)
+        );
+        assert_eq!(match_video(&video, "sandstorm", MatchMode::Fuzzy), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_videos() -> Result<()> {
+        let provider = crate::provider::InMemoryProvider::from_fixture(
+            r#"{
+                "kind": "youtube#videoListResponse",
+                "items": [
+                    { "kind": "youtube#video", "id": "dQw4w9WgXcQ", "snippet": { "title": "Never Gonna Give You Up" }, "contentDetails": { "duration": "PT3M33S" } },
+                    { "kind": "youtube#video", "id": "y6120QOlsfU", "snippet": { "title": "Sandstorm" } },
+                    { "kind": "youtube#video", "id": "a-b_c-d_e-f", "snippet": { "title": "Never Gonna Let You Down" } }
+                ]
+            }"#,
+        )?;
+
+        let videos = search_videos("never gonna", 10, &provider).await?;
+        let ids = videos.iter().map(Video::id).collect::<Vec<&str>>();
+        assert_eq!(ids, vec!["dQw4w9WgXcQ", "a-b_c-d_e-f"]);
+        assert_eq!(videos[0].duration_seconds(), Some(213));
+
+        assert_eq!(search_videos("never gonna", 1, &provider).await?.len(), 1);
+        assert!(search_videos("darude", 10, &provider).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("gonna", "gonna"), 0);
+        assert_eq!(edit_distance("gonna", "gona"), 1);
+        assert_eq!(edit_distance("titel", "title"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
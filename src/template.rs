@@ -0,0 +1,450 @@
+//! User-defined output templates
+//!
+//! A template is plain text containing `{field}` placeholders, e.g. `"{index}. {title} [{duration}] {url}"`.
+//! Literal braces are written as `{{` and `}}`, `\n` and `\t` insert a line break and a tab.
+//!
+//! Video fields: `index`, `id`, `title`, `url`, `published_at`, `date`, `availability`, `duration`,
+//! `duration_seconds`, `channel_title`, `channel_id`, `view_count`, `like_count`, `description`, `tags` and `thumbnail`.
+//!
+//! Playlist fields: `playlist_title`, `num_items`, `total_duration`, `total_duration_seconds`, `urls`, `playlist_urls` and `videos`.
+//! `urls` is the plain list of URLs, `playlist_urls` the labeled list used by the default layout
+//! (`playlist URL: -` if empty, one `part i/N` line per URL if the playlist is split into several parts).
+//!
+//! Fields that don't apply (e.g. video fields when listing playlists) render as an empty string,
+//! unknown values (e.g. the duration of a video that wasn't fetched yet) render as `unknown`.
+
+use crate::playlist::{Availability, Playlist, Video};
+use crate::{prelude::*, utils};
+use std::str::FromStr;
+
+/// Layout of a single video used by `Display for Video`
+pub const DEFAULT_VIDEO_TEMPLATE: &str =
+    "{title}\n\tID: {id}\n\tPublished at: {date}\n\tDuration: {duration}\n\tURL: {url}";
+
+/// Layout of a whole playlist used by `Display for Playlist`
+pub const DEFAULT_PLAYLIST_TEMPLATE: &str = "{playlist_title}\n----------\nlength: {num_items}\ntotal duration: {total_duration}\nvideos: \n{videos}\n\n{playlist_urls}";
+
+/// Thumbnail qualities from best to worst, used by the `thumbnail` field
+const THUMBNAIL_QUALITIES: [&str; 5] = ["maxres", "standard", "high", "medium", "default"];
+
+/// Value inserted for a placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Index,
+    Id,
+    Title,
+    Url,
+    PublishedAt,
+    Date,
+    Availability,
+    Duration,
+    DurationSeconds,
+    ChannelTitle,
+    ChannelId,
+    ViewCount,
+    LikeCount,
+    Description,
+    Tags,
+    Thumbnail,
+    PlaylistTitle,
+    NumItems,
+    TotalDuration,
+    TotalDurationSeconds,
+    Urls,
+    PlaylistUrls,
+    Videos,
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let field = match s {
+            "index" => Field::Index,
+            "id" => Field::Id,
+            "title" => Field::Title,
+            "url" => Field::Url,
+            "published_at" => Field::PublishedAt,
+            "date" => Field::Date,
+            "availability" => Field::Availability,
+            "duration" => Field::Duration,
+            "duration_seconds" => Field::DurationSeconds,
+            "channel_title" => Field::ChannelTitle,
+            "channel_id" => Field::ChannelId,
+            "view_count" => Field::ViewCount,
+            "like_count" => Field::LikeCount,
+            "description" => Field::Description,
+            "tags" => Field::Tags,
+            "thumbnail" => Field::Thumbnail,
+            "playlist_title" => Field::PlaylistTitle,
+            "num_items" => Field::NumItems,
+            "total_duration" => Field::TotalDuration,
+            "total_duration_seconds" => Field::TotalDurationSeconds,
+            "urls" => Field::Urls,
+            "playlist_urls" => Field::PlaylistUrls,
+            "videos" => Field::Videos,
+            _ => return Err(Error::InvalidTemplate(format!("unknown field `{{{}}}`", s))),
+        };
+        Ok(field)
+    }
+}
+
+/// Part of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// Everything a template can refer to while rendering
+#[derive(Debug, Default, Clone, Copy)]
+struct Context<'a> {
+    playlist: Option<&'a Playlist>,
+    /// 1-based position of `video` in `playlist`
+    index: Option<usize>,
+    video: Option<&'a Video>,
+}
+
+/// Parsed output template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(Error::InvalidTemplate(format!(
+                                    "unclosed `{{{}`",
+                                    name
+                                )))
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(name.trim().parse()?));
+                }
+                '}' => {
+                    return Err(Error::InvalidTemplate(
+                        "unmatched `}`, use `}}` for a literal brace".into(),
+                    ))
+                }
+                '\\' => match chars.peek() {
+                    Some('n') => {
+                        chars.next();
+                        literal.push('\n');
+                    }
+                    Some('t') => {
+                        chars.next();
+                        literal.push('\t');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        literal.push('\\');
+                    }
+                    _ => literal.push('\\'),
+                },
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl Template {
+    /// Return the template used by `Display for Video`
+    pub fn default_video() -> Self {
+        DEFAULT_VIDEO_TEMPLATE
+            .parse()
+            .expect("Default video template is valid")
+    }
+
+    /// Return the template used by `Display for Playlist`
+    pub fn default_playlist() -> Self {
+        DEFAULT_PLAYLIST_TEMPLATE
+            .parse()
+            .expect("Default playlist template is valid")
+    }
+
+    /// Render a single video without playlist context
+    /// * `video` - video to render
+    pub fn render_video(&self, video: &Video) -> String {
+        self.render(&Context {
+            video: Some(video),
+            ..Default::default()
+        })
+    }
+
+    /// Render a single video of a playlist
+    /// * `playlist` - playlist containing the video
+    /// * `index` - 0-based position of the video in `playlist`
+    pub fn render_playlist_video(&self, playlist: &Playlist, index: usize) -> String {
+        self.render(&Context {
+            playlist: Some(playlist),
+            index: Some(index + 1),
+            video: playlist.videos().get(index),
+        })
+    }
+
+    /// Render a playlist, video fields are left empty
+    /// * `playlist` - playlist to render
+    pub fn render_playlist(&self, playlist: &Playlist) -> String {
+        self.render(&Context {
+            playlist: Some(playlist),
+            ..Default::default()
+        })
+    }
+
+    fn render(&self, context: &Context) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.to_string(),
+                Segment::Field(field) => render_field(*field, context),
+            })
+            .collect()
+    }
+}
+
+/// Return the value of `field` in `context`
+fn render_field(field: Field, context: &Context) -> String {
+    let unknown = || "unknown".to_string();
+    let count = |count: Option<u64>| count.map(|count| count.to_string());
+
+    if let Some(video) = context.video {
+        let value = match field {
+            Field::Index => context.index.map(|index| index.to_string()),
+            Field::Id => Some(video.id().to_string()),
+            Field::Title => Some(match video.availability() {
+                Availability::Unavailable => "[unavailable]".to_string(),
+                _ => video.title().to_string(),
+            }),
+            Field::Url => Some(video.url().to_string()),
+            Field::PublishedAt => Some(video.published_at().to_string()),
+            Field::Date => video.published_at().split('T').next().map(String::from),
+            Field::Availability => Some(
+                match video.availability() {
+                    Availability::Available => "available",
+                    Availability::Unavailable => "unavailable",
+                    Availability::Unfetched => "unfetched",
+                }
+                .to_string(),
+            ),
+            Field::Duration => Some(
+                video
+                    .duration_seconds()
+                    .map(utils::format_duration)
+                    .unwrap_or_else(unknown),
+            ),
+            Field::DurationSeconds => Some(count(video.duration_seconds()).unwrap_or_else(unknown)),
+            Field::ChannelTitle => Some(video.channel_title().to_string()),
+            Field::ChannelId => Some(video.channel_id().to_string()),
+            Field::ViewCount => Some(count(video.view_count()).unwrap_or_else(unknown)),
+            Field::LikeCount => Some(count(video.like_count()).unwrap_or_else(unknown)),
+            Field::Description => Some(video.description().to_string()),
+            Field::Tags => Some(video.tags().join(", ")),
+            Field::Thumbnail => THUMBNAIL_QUALITIES
+                .iter()
+                .find_map(|quality| video.thumbnails().get(*quality))
+                .cloned(),
+            _ => None,
+        };
+        if let Some(value) = value {
+            return value;
+        }
+    }
+
+    let Some(playlist) = context.playlist else {
+        return String::new();
+    };
+    match field {
+        Field::PlaylistTitle => playlist.title().to_string(),
+        Field::NumItems => playlist.num_items().to_string(),
+        Field::TotalDuration => utils::format_duration(playlist.total_duration()),
+        Field::TotalDurationSeconds => playlist.total_duration().to_string(),
+        Field::Urls => playlist.urls().join("\n"),
+        Field::PlaylistUrls => match playlist.urls() {
+            [] => "playlist URL: -".to_string(),
+            [url] => format!("playlist URL: {}", url),
+            urls => {
+                let parts = urls
+                    .iter()
+                    .enumerate()
+                    .map(|(i, url)| format!("\tpart {}/{}: {}", i + 1, urls.len(), url))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!("playlist URLs:\n{}", parts)
+            }
+        },
+        Field::Videos => {
+            let template = Template::default_video();
+            playlist
+                .videos()
+                .iter()
+                .map(|video| template.render_video(video).replace('\t', "\t\t"))
+                .map(|video_string| format!("\t{}", video_string))
+                .collect::<Vec<String>>()
+                .join("\n\n")
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        let template: Template = "{index}. {title} [{ duration }] {{{url}}}\\n"
+            .parse()
+            .expect("Template is valid");
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Field(Field::Index),
+                Segment::Literal(". ".into()),
+                Segment::Field(Field::Title),
+                Segment::Literal(" [".into()),
+                Segment::Field(Field::Duration),
+                Segment::Literal("] {".into()),
+                Segment::Field(Field::Url),
+                Segment::Literal("}\n".into()),
+            ]
+        );
+
+        for invalid in ["{foo}", "{title", "title}"] {
+            assert!(
+                matches!(invalid.parse::<Template>(), Err(Error::InvalidTemplate(_))),
+                "{invalid}"
+            );
+        }
+        Template::default_video();
+        Template::default_playlist();
+    }
+
+    #[test]
+    fn test_render_template() {
+        let mut playlist = Playlist::new("music");
+        playlist.push_videos(vec![
+            Video::from_entry(
+                "dQw4w9WgXcQ",
+                "Never Gonna Give You Up",
+                Some(213),
+                "Rick Astley",
+            )
+            .with_published_at("2009-10-25T06:57:33Z"),
+            Video::from("y6120QOlsfU".to_string()),
+        ]);
+
+        let template: Template = "{index}. {title} [{duration}] {url}"
+            .parse()
+            .expect("Template is valid");
+        assert_eq!(
+            template.render_playlist_video(&playlist, 0),
+            "1. Never Gonna Give You Up [3:33] https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        );
+        assert_eq!(
+            template.render_playlist_video(&playlist, 1),
+            "2.  [unknown] https://www.youtube.com/watch?v=y6120QOlsfU"
+        );
+
+        let template: Template = "{playlist_title}: {num_items} videos, {total_duration} {title}"
+            .parse()
+            .expect("Template is valid");
+        assert_eq!(
+            template.render_playlist(&playlist),
+            "music: 2 videos, 3:33 "
+        );
+        assert_eq!(
+            template.render_playlist_video(&playlist, 0),
+            "music: 2 videos, 3:33 Never Gonna Give You Up"
+        );
+
+        let template: Template = "{date} by {channel_title}"
+            .parse()
+            .expect("Template is valid");
+        assert_eq!(
+            template.render_video(&playlist.videos()[0]),
+            "2009-10-25 by Rick Astley"
+        );
+    }
+
+    /// `Display for Playlist` as it was before templates were introduced
+    fn display_before_templates(playlist: &Playlist) -> String {
+        let videos = playlist
+            .videos()
+            .iter()
+            .map(|video| format!("{}", video).replace('\t', "\t\t"))
+            .map(|video_string| format!("\t{}", video_string))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        let urls = match playlist.urls() {
+            [] => "playlist URL: -".to_string(),
+            [url] => format!("playlist URL: {}", url),
+            urls => {
+                let parts = urls
+                    .iter()
+                    .enumerate()
+                    .map(|(i, url)| format!("\tpart {}/{}: {}", i + 1, urls.len(), url))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!("playlist URLs:\n{}", parts)
+            }
+        };
+        format!(
+            "{}\n----------\nlength: {}\ntotal duration: {}\nvideos: \n{}\n\n{}",
+            playlist.title(),
+            playlist.num_items(),
+            utils::format_duration(playlist.total_duration()),
+            videos,
+            urls
+        )
+    }
+
+    #[test]
+    fn test_default_playlist_template() {
+        let empty = Playlist::new("empty");
+        assert_eq!(empty.to_string(), display_before_templates(&empty));
+        assert!(empty.to_string().ends_with("playlist URL: -"));
+
+        let mut chunked = Playlist::new("chunked");
+        chunked.push_videos(
+            (0..51)
+                .map(|i| Video::from(format!("video{:06}", i)))
+                .collect(),
+        );
+        assert_eq!(chunked.urls().len(), 2);
+        assert_eq!(chunked.to_string(), display_before_templates(&chunked));
+        assert!(chunked.to_string().contains("\tpart 1/2: "));
+        assert!(chunked.to_string().contains("\tpart 2/2: "));
+    }
+}
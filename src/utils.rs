@@ -2,27 +2,48 @@
 
 use crate::prelude::*;
 use directories::BaseDirs;
-use std::{ffi::OsStr, fs, path::PathBuf};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Return file content if file exists, else create the file
+/// Replace the content of a file without ever leaving it partially written
 ///
-/// Try to read file content to `String`
-/// If the file exists, return content
-/// If the file doesn't exist, try to create it and return `None`
+/// The content is written to a temporary file in the same directory, flushed to disk and then renamed,
+/// so readers see either the old or the new content, even if the process is interrupted.
 /// * `file_path` - full file path (e.g. "./test.json")
-pub fn load_or_create_file(file_path: PathBuf) -> Result<Option<String>> {
-    let file_path = expand_path_aliases(file_path);
-
-    match fs::read_to_string(&file_path) {
-        Ok(content) => Ok(Some(content)),
-        Err(error) => match error.kind() {
-            std::io::ErrorKind::NotFound => {
-                fs::File::create(&file_path)?;
-                Ok(None)
-            }
-            _ => Err(Error::from(error)),
-        },
+/// * `content` - new content of the file
+pub fn write_atomic(file_path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let directory = file_path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = file_path
+        .file_name()
+        .ok_or_else(|| Error::StringFromPathBuf(format!("{:?}", file_path)))?;
+    let mut temp_file_name = OsString::from(".");
+    temp_file_name.push(file_name);
+    temp_file_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_file_path = directory.join(temp_file_name);
+
+    let result = (|| {
+        let mut temp_file = fs::File::create(&temp_file_path)?;
+        temp_file.write_all(content.as_ref())?;
+        temp_file.sync_all()?;
+        fs::rename(&temp_file_path, file_path)
+    })();
+    if let Err(error) = result {
+        let _ = fs::remove_file(&temp_file_path);
+        return Err(Error::from(error));
+    }
+
+    // Persist the rename itself, not supported on every platform
+    if let Ok(directory) = fs::File::open(directory) {
+        let _ = directory.sync_all();
     }
+
+    Ok(())
 }
 
 /// Return a `PathBuf`that has common aliases in file paths replaced with the paths of user-invisible standard directories
@@ -62,3 +83,235 @@ pub fn expand_path_aliases(file_path: PathBuf) -> PathBuf {
         file_path
     }
 }
+
+/// Return the number of seconds of an ISO 8601 duration (e.g. "PT1H2M3S")
+///
+/// Supports the designators YouTube uses (weeks, days, hours, minutes and seconds).
+/// Return `None` if `duration` is not a valid duration.
+/// * `duration` - ISO 8601 duration
+pub fn parse_iso8601_duration(duration: &str) -> Option<u64> {
+    let duration = duration.strip_prefix('P')?;
+    let (date, time) = duration.split_once('T').unwrap_or((duration, ""));
+    if date.is_empty() && time.is_empty() {
+        return None;
+    }
+
+    let sum_components = |components: &str, unit_seconds: fn(char) -> Option<u64>| {
+        let mut total = 0;
+        let mut number = String::new();
+        for c in components.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+            } else {
+                let value: u64 = number.parse().ok()?;
+                total = value
+                    .checked_mul(unit_seconds(c)?)
+                    .and_then(|seconds| seconds.checked_add(total))?;
+                number.clear();
+            }
+        }
+        number.is_empty().then_some(total)
+    };
+
+    let date_seconds = sum_components(date, |unit| match unit {
+        'W' => Some(7 * 24 * 60 * 60),
+        'D' => Some(24 * 60 * 60),
+        _ => None,
+    })?;
+    let time_seconds = sum_components(time, |unit| match unit {
+        'H' => Some(60 * 60),
+        'M' => Some(60),
+        'S' => Some(1),
+        _ => None,
+    })?;
+
+    date_seconds.checked_add(time_seconds)
+}
+
+/// Return the ISO 8601 representation of a duration, e.g. "PT3M33S"
+/// * `seconds` - duration in seconds
+pub fn format_iso8601_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let seconds = seconds % 60;
+
+    let mut duration = String::from("PT");
+    if hours > 0 {
+        duration.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        duration.push_str(&format!("{}M", minutes));
+    }
+    if seconds > 0 || (hours == 0 && minutes == 0) {
+        duration.push_str(&format!("{}S", seconds));
+    }
+    duration
+}
+
+/// Return a human readable representation of a duration, e.g. "3:33" or "1:02:03"
+/// * `seconds` - duration in seconds
+pub fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let seconds = seconds % 60;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Return a human readable UTC representation of a point in time, e.g. "2024-05-17 09:41"
+/// * `time` - e.g. the modification time of a file
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+
+    // Convert days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        (seconds % 3600) / 60
+    )
+}
+
+/// Maximum length of a slug in characters, keeps file names well below common file system limits
+const MAX_SLUG_LENGTH: usize = 64;
+
+/// Return a file name safe representation of `title`, e.g. "My Music: 2024/25" becomes "my-music-2024-25"
+///
+/// Letters and digits are lowercased, every other run of characters becomes a single `-`.
+/// Titles without letters or digits become "playlist".
+/// Different titles may have the same slug, so it must not be used to identify a playlist.
+/// * `title` - e.g. the title of a playlist
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if slug.chars().count() >= MAX_SLUG_LENGTH {
+            break;
+        }
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    match slug.trim_end_matches('-') {
+        "" => "playlist".to_string(),
+        slug => slug.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(parse_iso8601_duration("PT3M33S"), Some(213));
+        assert_eq!(parse_iso8601_duration("PT1H2M3S"), Some(3723));
+        assert_eq!(parse_iso8601_duration("PT45S"), Some(45));
+        assert_eq!(parse_iso8601_duration("PT2H"), Some(7200));
+        assert_eq!(parse_iso8601_duration("P1DT1S"), Some(86401));
+        assert_eq!(parse_iso8601_duration("P1W"), Some(604800));
+        assert_eq!(parse_iso8601_duration("P0D"), Some(0));
+
+        assert_eq!(parse_iso8601_duration(""), None);
+        assert_eq!(parse_iso8601_duration("P"), None);
+        assert_eq!(parse_iso8601_duration("PT"), None);
+        assert_eq!(parse_iso8601_duration("PT3X"), None);
+        assert_eq!(parse_iso8601_duration("PT3"), None);
+        assert_eq!(parse_iso8601_duration("3M33S"), None);
+        assert_eq!(parse_iso8601_duration("PT9999999999999999H"), None);
+        assert_eq!(parse_iso8601_duration("PT99999999999999999999S"), None);
+        assert_eq!(
+            parse_iso8601_duration(&format!("PT{}S{}S", u64::MAX, 1)),
+            None
+        );
+        assert_eq!(parse_iso8601_duration(&format!("P1DT{}S", u64::MAX)), None);
+    }
+
+    #[test]
+    fn test_format_iso8601_duration() {
+        assert_eq!(format_iso8601_duration(0), "PT0S");
+        assert_eq!(format_iso8601_duration(213), "PT3M33S");
+        assert_eq!(format_iso8601_duration(3600), "PT1H");
+        assert_eq!(format_iso8601_duration(3723), "PT1H2M3S");
+        assert_eq!(
+            parse_iso8601_duration(&format_iso8601_duration(93784)),
+            Some(93784)
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0:00");
+        assert_eq!(format_duration(213), "3:33");
+        assert_eq!(format_duration(3723), "1:02:03");
+        assert_eq!(format_duration(36000), "10:00:00");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let timestamp = |seconds| UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(timestamp(1715938860)), "2024-05-17 09:41");
+        assert_eq!(format_timestamp(timestamp(951868740)), "2000-02-29 23:59");
+    }
+
+    #[test]
+    fn test_write_atomic() -> Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("oxysound-atomic-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let file_path = directory.join("music.json");
+
+        write_atomic(&file_path, "old")?;
+        write_atomic(&file_path, "new")?;
+        assert_eq!(fs::read_to_string(&file_path)?, "new");
+        assert_eq!(fs::read_dir(&directory)?.count(), 1);
+
+        fs::remove_dir_all(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("music"), "music");
+        assert_eq!(slugify("My Music: 2024/25"), "my-music-2024-25");
+        assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify(".hidden"), "hidden");
+        assert_eq!(slugify("Ünïcödé"), "ünïcödé");
+        assert_eq!(slugify("..."), "playlist");
+        assert_eq!(slugify(""), "playlist");
+        assert_eq!(slugify(&"a".repeat(100)).len(), MAX_SLUG_LENGTH);
+        assert_eq!(
+            slugify(&format!("{}-b", "a".repeat(MAX_SLUG_LENGTH - 1))),
+            "a".repeat(MAX_SLUG_LENGTH - 1)
+        );
+    }
+}
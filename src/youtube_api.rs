@@ -2,9 +2,12 @@
 
 use crate::config::Config;
 use crate::prelude::*;
+use crate::provider::MetadataProvider;
+use futures::{stream, StreamExt, TryStreamExt};
 use reqwest::header;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Data structure for snippet
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -17,6 +20,35 @@ pub struct ResponseSnippet {
     pub channel_title: Option<String>,
     pub tags: Option<Vec<String>>,
     pub category_id: Option<String>,
+    pub thumbnails: Option<BTreeMap<String, ResponseThumbnail>>,
+}
+
+/// Data structure for a thumbnail
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseThumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Data structure for content details
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseContentDetails {
+    /// ISO 8601 duration, e.g. "PT3M33S"
+    pub duration: Option<String>,
+}
+
+/// Data structure for statistics
+///
+/// The API returns counts as strings
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseStatistics {
+    pub view_count: Option<String>,
+    pub like_count: Option<String>,
+    pub comment_count: Option<String>,
 }
 
 /// Data structure for a response item
@@ -26,6 +58,8 @@ pub struct ResponseItem {
     pub kind: String,
     pub id: String,
     pub snippet: ResponseSnippet,
+    pub content_details: Option<ResponseContentDetails>,
+    pub statistics: Option<ResponseStatistics>,
 }
 
 /// Data structure for API responses
@@ -36,28 +70,209 @@ pub struct Response {
     pub items: Vec<ResponseItem>,
 }
 
-fn create_videos_request(video_ids: &[String]) -> Result<String> {
-    const API_URL: &str = "https://youtube.googleapis.com/youtube/v3/videos?part=snippet%2CcontentDetails%2Cstatistics";
-    let config: Config = confy::load("oxysound", "config")?;
-    let api_key = config.youtube_api_key;
-    let key_url = format!("&key={}", api_key);
+/// Data structure for the content details of a playlist item
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemContentDetails {
+    pub video_id: String,
+}
+
+/// Data structure for a playlist item
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItem {
+    pub content_details: PlaylistItemContentDetails,
+}
+
+/// Data structure for a single page of a `playlistItems` response
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemsResponse {
+    pub next_page_token: Option<String>,
+    pub items: Vec<PlaylistItem>,
+}
+
+/// Data structure for the ID of a search result
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultId {
+    pub kind: String,
+    pub video_id: Option<String>,
+}
 
-    let id_url = format!("&id={}", video_ids.join(","));
+/// Data structure for a search result
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub id: SearchResultId,
+}
 
-    Ok(format!("{}{}{}", API_URL, id_url, key_url))
+/// Data structure for a `search` response
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    pub items: Vec<SearchResult>,
 }
 
-pub async fn make_video_request(video_ids: &[String]) -> Result<Response> {
-    let url = create_videos_request(video_ids)?;
-    let client = Client::new();
-    let response = client
-        .get(&url)
-        .header(header::ACCEPT, "application/json")
-        .send()
-        .await?
-        .json()
-        .await?;
-    Ok(response)
+/// Maximum number of video IDs the API accepts per `videos` request
+const MAX_IDS_PER_REQUEST: usize = 50;
+
+/// Maximum number of `videos` requests in flight at the same time
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Metadata provider backed by YouTube's Data API
+#[derive(Debug, Clone)]
+pub struct YouTubeProvider {
+    client: Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl YouTubeProvider {
+    /// Create a provider using the API base URL and key configured in `config`
+    pub fn new(config: &Config) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: config.youtube_api_url.trim_end_matches('/').to_string(),
+            api_key: config.youtube_api_key.to_string(),
+        }
+    }
+
+    fn create_videos_request(&self, video_ids: &[String]) -> String {
+        const VIDEOS_PATH: &str = "/videos?part=snippet%2CcontentDetails%2Cstatistics";
+        let key_url = format!("&key={}", self.api_key);
+
+        let id_url = format!("&id={}", video_ids.join(","));
+
+        format!("{}{}{}{}", self.base_url, VIDEOS_PATH, id_url, key_url)
+    }
+
+    fn create_playlist_items_request(&self, playlist_id: &str, page_token: Option<&str>) -> String {
+        const PLAYLIST_ITEMS_PATH: &str = "/playlistItems?part=contentDetails&maxResults=50";
+        let key_url = format!("&key={}", self.api_key);
+
+        let id_url = format!("&playlistId={}", playlist_id);
+        let page_url = page_token
+            .map(|page_token| format!("&pageToken={}", page_token))
+            .unwrap_or_default();
+
+        format!(
+            "{}{}{}{}{}",
+            self.base_url, PLAYLIST_ITEMS_PATH, id_url, page_url, key_url
+        )
+    }
+
+    fn create_search_request(&self, query: &str, limit: usize) -> String {
+        const SEARCH_PATH: &str = "/search?part=snippet&type=video";
+        let key_url = format!("&key={}", self.api_key);
+
+        let limit_url = format!("&maxResults={}", limit);
+        let query_url = format!("&q={}", encode_query_value(query));
+
+        format!(
+            "{}{}{}{}{}",
+            self.base_url, SEARCH_PATH, limit_url, query_url, key_url
+        )
+    }
+
+    /// Return one request URL per chunk of at most `MAX_IDS_PER_REQUEST` video IDs
+    fn create_videos_requests(&self, video_ids: &[String]) -> Vec<String> {
+        video_ids
+            .chunks(MAX_IDS_PER_REQUEST)
+            .map(|chunk| self.create_videos_request(chunk))
+            .collect()
+    }
+
+    async fn send_request<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response)
+    }
+}
+
+impl MetadataProvider for YouTubeProvider {
+    /// Request meta data for all `video_ids`
+    ///
+    /// IDs are split into chunks respecting the API's limit per request.
+    /// The chunks are requested concurrently over a shared `Client` and
+    /// the response items are merged in the order of the chunks.
+    /// * `video_ids` - list of video IDs
+    async fn fetch_videos(&self, video_ids: &[String]) -> Result<Vec<ResponseItem>> {
+        let responses: Vec<Response> = stream::iter(self.create_videos_requests(video_ids))
+            .map(|url| async move { self.send_request(&url).await })
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+        Ok(responses
+            .into_iter()
+            .flat_map(|response| response.items)
+            .collect())
+    }
+
+    /// Request the IDs of all videos in a YouTube playlist
+    ///
+    /// Pages are requested one after another following `nextPageToken`.
+    /// * `playlist_id` - ID of the YouTube playlist
+    async fn fetch_playlist_items(&self, playlist_id: &str) -> Result<Vec<String>> {
+        let mut video_ids = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let url = self.create_playlist_items_request(playlist_id, page_token.as_deref());
+            let response: PlaylistItemsResponse = self.send_request(&url).await?;
+
+            video_ids.extend(
+                response
+                    .items
+                    .into_iter()
+                    .map(|item| item.content_details.video_id),
+            );
+
+            match response.next_page_token {
+                Some(next_page_token) => page_token = Some(next_page_token),
+                None => break,
+            }
+        }
+
+        Ok(video_ids)
+    }
+
+    /// Search for videos matching `query`
+    ///
+    /// Results are ranked by relevance, as returned by the API.
+    /// * `query` - search terms
+    /// * `limit` - maximum number of results (at most 50)
+    async fn search_videos(&self, query: &str, limit: usize) -> Result<Vec<String>> {
+        let url = self.create_search_request(query, limit);
+        let response: SearchResponse = self.send_request(&url).await?;
+
+        Ok(response
+            .items
+            .into_iter()
+            .filter_map(|item| item.id.video_id)
+            .collect())
+    }
+}
+
+/// Percent-encode `value` for use in a URL's query
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -82,25 +297,91 @@ mod tests {
                     channel_title: Some("Rick Astley".into()),
                     tags: Some(vec!["".into()]),
                     category_id: Some("10".into()),
+                    thumbnails: None,
                 },
+                content_details: Some(ResponseContentDetails {
+                    duration: Some("PT3M33S".into()),
+                }),
+                statistics: None,
             }],
         }
     }
 
+    fn test_provider() -> YouTubeProvider {
+        YouTubeProvider::new(&Config {
+            youtube_api_key: "key".into(),
+            ..Default::default()
+        })
+    }
+
     #[test]
-    #[ignore = "test requires API key"]
     fn test_create_video_request() {
-        let request = create_videos_request(&["dQw4w9WgXcQ".into()])
-            .expect("Expect config to load successfully for test");
+        let provider = test_provider();
+        let request = provider.create_videos_request(&["dQw4w9WgXcQ".into()]);
         assert!(request.contains("https://youtube.googleapis.com/youtube/v3/videos?part=snippet%2CcontentDetails%2Cstatistics"));
         assert!(request.contains("&id=dQw4w9WgXcQ"));
+        assert!(request.contains("&key=key"));
 
-        let request = create_videos_request(&["dQw4w9WgXcQ".into(), "y6120QOlsfU".into()])
-            .expect("Expect config to load successfully for test");
+        let request = provider.create_videos_request(&["dQw4w9WgXcQ".into(), "y6120QOlsfU".into()]);
         assert!(request.contains("https://youtube.googleapis.com/youtube/v3/videos?part=snippet%2CcontentDetails%2Cstatistics"));
         assert!(request.contains("&id=dQw4w9WgXcQ,y6120QOlsfU"));
     }
 
+    #[test]
+    fn test_create_video_requests() {
+        let ids: Vec<String> = (0..120).map(|i| format!("id_{}", i)).collect();
+        let provider = test_provider();
+        let requests = provider.create_videos_requests(&ids);
+
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains(&format!("&id={}&", ids[0..50].join(","))));
+        assert!(requests[1].contains(&format!("&id={}&", ids[50..100].join(","))));
+        assert!(requests[2].contains(&format!("&id={}&", ids[100..120].join(","))));
+
+        assert!(provider.create_videos_requests(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_create_playlist_items_request() {
+        let provider = test_provider();
+
+        let request =
+            provider.create_playlist_items_request("PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG", None);
+        assert_eq!(request, "https://youtube.googleapis.com/youtube/v3/playlistItems?part=contentDetails&maxResults=50&playlistId=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG&key=key");
+
+        let request = provider.create_playlist_items_request(
+            "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG",
+            Some("EAAaBlBUOkNESQ"),
+        );
+        assert!(request.contains("&pageToken=EAAaBlBUOkNESQ&"));
+    }
+
+    #[test]
+    fn test_create_search_request() {
+        let provider = test_provider();
+
+        let request = provider.create_search_request("rick astley & friends", 5);
+        assert_eq!(request, "https://youtube.googleapis.com/youtube/v3/search?part=snippet&type=video&maxResults=5&q=rick%20astley%20%26%20friends&key=key");
+
+        assert_eq!(
+            encode_query_value("never gonna-give_you~up."),
+            "never%20gonna-give_you~up."
+        );
+        assert_eq!(encode_query_value("ä"), "%C3%A4");
+    }
+
+    #[test]
+    fn test_configured_base_url() {
+        let provider = YouTubeProvider::new(&Config {
+            youtube_api_key: "key".into(),
+            youtube_api_url: "http://localhost:8080/youtube/v3/".into(),
+            ..Default::default()
+        });
+        let request = provider.create_videos_request(&["dQw4w9WgXcQ".into()]);
+
+        assert_eq!(request, "http://localhost:8080/youtube/v3/videos?part=snippet%2CcontentDetails%2Cstatistics&id=dQw4w9WgXcQ&key=key");
+    }
+
     #[tokio::test]
     #[ignore = "test requires API key"]
     async fn test_make_video_request() {
@@ -110,16 +391,17 @@ mod tests {
             .first()
             .expect("Has exactly one item");
 
-        let response = make_video_request(&["dQw4w9WgXcQ".into()])
+        let config: Config =
+            confy::load("oxysound", "config").expect("Expect config to load successfully");
+        let items = YouTubeProvider::new(&config)
+            .fetch_videos(&["dQw4w9WgXcQ".into()])
             .await
             .expect("Expect request to succeed for testing purposes");
-        let first_item = response
-            .items
+        let first_item = items
             .first()
             .expect("Should have first item if request succeeded");
 
-        assert_eq!(response.kind, expected_response.kind);
-        assert!(!response.items.is_empty());
+        assert!(!items.is_empty());
         assert_eq!(first_item.id, expected_first_item.id);
         assert_eq!(first_item.kind, expected_first_item.kind);
         assert_eq!(
@@ -139,5 +421,15 @@ mod tests {
             first_item.snippet.category_id,
             expected_first_item.snippet.category_id
         );
+        assert_eq!(
+            first_item
+                .content_details
+                .as_ref()
+                .and_then(|details| details.duration.as_ref()),
+            expected_first_item
+                .content_details
+                .as_ref()
+                .and_then(|details| details.duration.as_ref())
+        );
     }
 }